@@ -0,0 +1,26 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use logback::{LogEvent, LogEventStream};
+
+fn read(data: &[u8]) -> Vec<LogEvent> {
+    LogEventStream::new(data)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn format(c: &mut Criterion) {
+    let short = read(include_bytes!("../tests/fixtures/five_events.ser"));
+    let long = read(include_bytes!("../tests/fixtures/long_messages.ser"));
+    c.bench_function("short template", |b| {
+        b.iter(|| black_box(&short[0]).message().len())
+    });
+    c.bench_function("long template", |b| {
+        b.iter(|| black_box(&long[0]).message().len())
+    });
+    c.bench_function("long message without anchors", |b| {
+        b.iter(|| black_box(&long[1]).message().len())
+    });
+}
+
+criterion_group!(benches, format);
+criterion_main!(benches);
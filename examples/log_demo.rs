@@ -1,78 +1,79 @@
 use gumdrop::Options;
-use std::{fs::File, io::Read, net::TcpStream, path::PathBuf, thread, time::Duration};
+use std::{fs::File, io, path::PathBuf};
 use yansi::{Color, Style};
 
-use logback::LogLevel;
+use logback::{
+    Error, EventStreamExt, LogEvent, LogEventStream, LogLevel, Pattern, RetryConfig, WordRegistry,
+};
+
+/// The marker logged once an application has finished starting up
+const STARTUP_COMPLETE: &str = "STARTUP_COMPLETE";
 
 pub fn main() {
     let command = Command::parse_args_default_or_exit();
+    let threshold = command.level.unwrap_or(LogLevel::Info);
 
-    let src: Box<dyn Read> = if let Some(file) = command.file {
-        Box::new(File::open(file).unwrap())
+    if command.stdin {
+        let events = LogEventStream::from_stdin().expect("failed to read from stdin");
+        print_events(events, threshold, command.startup);
+    } else if let Some(file) = command.file {
+        let events =
+            LogEventStream::new(File::open(file).unwrap()).expect("failed to create parser");
+        print_events(events, threshold, command.startup);
     } else {
         let host = command.host.as_deref().unwrap_or("localhost");
         let port = command.port.unwrap_or(6750);
-        loop {
-            if let Ok(sock) = TcpStream::connect((host, port)) {
-                println!("Connected to server");
-                break Box::new(sock);
-            }
-            thread::sleep(Duration::from_millis(200));
-        }
-    };
-
-    let mut reader = jaded::Parser::new(src).expect("failed to create parser");
+        let events = logback::connect_tcp((host, port), &RetryConfig::default())
+            .expect("failed to connect to server");
+        println!("Connected to server");
+        print_events(events, threshold, command.startup);
+    }
+}
 
-    let mut count = 0;
-    let threshold = command.level.unwrap_or(LogLevel::Info);
+fn print_events<I>(events: I, threshold: LogLevel, startup: bool)
+where
+    I: Iterator<Item = Result<LogEvent, Error>>,
+{
+    let events: Box<dyn Iterator<Item = _>> = match startup {
+        true => Box::new(events.until_marker(STARTUP_COMPLETE)),
+        false => Box::new(events),
+    };
+    let words = WordRegistry::new().register("styled", |evt| {
+        let style = match evt.level {
+            LogLevel::Trace => Style::default().dimmed(),
+            LogLevel::Debug => Style::default(),
+            LogLevel::Info => Style::default().bold(),
+            LogLevel::Warn => Style::new(Color::Yellow),
+            LogLevel::Error => Style::new(Color::Red),
+            _ => Style::default(),
+        };
+        style.paint(evt.message()).to_string()
+    });
+    let pattern = Pattern::parse_with(
+        "%d{yyyy-MM-dd HH:mm:ss.SSS} %-5level %.-40logger - %styled%n",
+        &words,
+    )
+    .unwrap();
 
-    loop {
-        match reader.read_as::<logback::LogEvent>() {
-            Ok(evt) => {
-                if evt.level >= threshold {
-                    let style = match evt.level {
-                        LogLevel::Trace => Style::default().dimmed(),
-                        LogLevel::Debug => Style::default(),
-                        LogLevel::Info => Style::default().bold(),
-                        LogLevel::Warn => Style::new(Color::Yellow),
-                        LogLevel::Error => Style::new(Color::Red),
-                        _ => Style::default(),
-                    };
-                    let dt = evt.time();
-                    println!(
-                        "{} {} {} {:.40} - {}",
-                        dt.date(),
-                        dt.time(),
-                        evt.level,
-                        evt.logger_name,
-                        style.paint(evt.message())
-                    );
-                    if let Some(ex) = evt.throwable {
-                        println!("{}", ex.format_trace());
-                    }
-                }
-                count += 1;
-                if let Some(_) = &evt.marker {
-                    println!("Read {} messages", count);
-                    break;
-                }
-            }
-            Err(e) => {
-                println!("{}", e);
-                break;
-            }
-        }
+    let summary = logback::run_tail(events, threshold, &pattern, &mut io::stdout().lock())
+        .expect("failed to write to stdout");
+    if let Some(e) = summary.error {
+        println!("{}", e);
     }
+    println!("Read {} messages", summary.read);
 }
 
 #[derive(Debug, Default, Options)]
 struct Command {
     #[options(help = "Read log messages from file")]
     file: Option<PathBuf>,
+    #[options(help = "Read log messages piped to stdin")]
+    stdin: bool,
     #[options(help = "Connect to server to read messages")]
     host: Option<String>,
     #[options(help = "Server port broadcasting log messages - default: 6750")]
     port: Option<u16>,
+    #[options(help = "Stop reading once the application has started")]
     startup: bool,
     level: Option<LogLevel>,
 }
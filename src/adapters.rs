@@ -0,0 +1,585 @@
+use std::{
+    collections::BTreeMap,
+    mem,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use time::OffsetDateTime;
+
+use crate::{Clock, Error, LogEvent, Pipeline, SystemClock, Through};
+
+/// Adapters for iterators of events, eg a [`LogEventStream`](crate::LogEventStream)
+pub trait EventStreamExt: Iterator<Item = Result<LogEvent, Error>> + Sized {
+    /// Group consecutive events sharing the same value for the given MDC key
+    ///
+    /// A group is yielded when an event with a different value is read or at
+    /// the end of the stream so events for a key that are interleaved with
+    /// events for other keys end up in more than one group. By default,
+    /// consecutive events without the key are grouped together under `None`
+    /// (see [`GroupByMdc::skip_missing`]). Errors are passed through as they are
+    /// read without ending the current group.
+    fn group_by_mdc(self, key: &str) -> GroupByMdc<Self> {
+        GroupByMdc {
+            events: self,
+            key: key.into(),
+            skip_missing: false,
+            current: None,
+            group: vec![],
+        }
+    }
+
+    /// End the stream after the first event with the given marker
+    ///
+    /// The event with the marker is included. Markers referenced by an event's
+    /// marker also count (see [`LogEvent::has_marker`]).
+    fn until_marker(self, name: &str) -> UntilMarker<Self> {
+        UntilMarker {
+            events: self,
+            marker: name.into(),
+            done: false,
+        }
+    }
+
+    /// Count the events read so that throughput can be monitored, eg from
+    /// another thread using the [`Meter`] from [`Metered::meter`]
+    fn metered(self) -> Metered<Self> {
+        self.metered_with(SystemClock)
+    }
+
+    /// As [`EventStreamExt::metered`] but measuring the rate with the given
+    /// clock
+    fn metered_with<C: Clock + Send + Sync + 'static>(self, clock: C) -> Metered<Self> {
+        let start = clock.elapsed();
+        Metered {
+            events: self,
+            meter: Meter {
+                count: Arc::new(AtomicU64::new(0)),
+                clock: Arc::new(clock),
+                start,
+            },
+        }
+    }
+
+    /// Pair each event with the gap in sequence numbers before it, if any
+    ///
+    /// A gap is reported whenever an event's [sequence
+    /// number](LogEvent::sequence_number) doesn't follow on from the last one
+    /// seen, ie events were lost or arrived out of order. Events without a
+    /// sequence number never have a gap and don't affect the next one.
+    fn detect_gaps_by_sequence(self) -> SequenceGaps<Self> {
+        SequenceGaps {
+            events: self,
+            last: None,
+        }
+    }
+
+    /// Record the time each event was read, eg to measure the latency between
+    /// events being logged and processed
+    fn stamp_received(self) -> StampReceived<Self> {
+        self.stamp_received_with(SystemClock)
+    }
+
+    /// As [`EventStreamExt::stamp_received`] but taking the time from the
+    /// given clock
+    fn stamp_received_with<C: Clock>(self, clock: C) -> StampReceived<Self, C> {
+        StampReceived {
+            events: self,
+            clock,
+        }
+    }
+
+    /// Put events that arrive slightly out of order back into timestamp order
+    ///
+    /// Events are held until an event logged more than `window` after them has
+    /// been read, so events are delayed by up to the window (in event time).
+    /// Events more than the window out of order are not corrected. Events with
+    /// the same timestamp stay in the order they were read and errors are
+    /// passed through as soon as they are read.
+    fn reorder(self, window: Duration) -> Reorder<Self> {
+        Reorder {
+            events: self,
+            window: i64::try_from(window.as_millis()).unwrap_or(i64::MAX),
+            pending: BTreeMap::new(),
+            read: 0,
+            latest: i64::MIN,
+            done: false,
+        }
+    }
+
+    /// Pass each event through a pipeline of transforms, skipping any events
+    /// that are dropped by it. Errors are passed through unchanged.
+    fn through(self, pipeline: Pipeline) -> Through<Self> {
+        Through {
+            events: self,
+            pipeline,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<LogEvent, Error>>> EventStreamExt for I {}
+
+/// Iterator over groups of events sharing an MDC value
+///
+/// See [`EventStreamExt::group_by_mdc`].
+pub struct GroupByMdc<I> {
+    events: I,
+    key: String,
+    skip_missing: bool,
+    current: Option<String>,
+    group: Vec<LogEvent>,
+}
+
+impl<I> GroupByMdc<I> {
+    /// Drop events that don't have the MDC key instead of grouping them
+    pub fn skip_missing(mut self) -> Self {
+        self.skip_missing = true;
+        self
+    }
+
+    fn flush(&mut self, next: Option<String>) -> (Option<String>, Vec<LogEvent>) {
+        (
+            mem::replace(&mut self.current, next),
+            mem::take(&mut self.group),
+        )
+    }
+}
+
+impl<I: Iterator<Item = Result<LogEvent, Error>>> Iterator for GroupByMdc<I> {
+    type Item = Result<(Option<String>, Vec<LogEvent>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let evt = match self.events.next() {
+                Some(Ok(evt)) => evt,
+                Some(Err(e)) => return Some(Err(e)),
+                None if self.group.is_empty() => return None,
+                None => return Some(Ok(self.flush(None))),
+            };
+            let value = evt.mdc.get(&self.key).cloned();
+            if value.is_none() && self.skip_missing {
+                continue;
+            }
+            if self.group.is_empty() || value == self.current {
+                self.current = value;
+                self.group.push(evt);
+            } else {
+                let group = self.flush(value);
+                self.group.push(evt);
+                return Some(Ok(group));
+            }
+        }
+    }
+}
+
+/// Iterator over the events up to and including one with a marker
+///
+/// See [`EventStreamExt::until_marker`].
+pub struct UntilMarker<I> {
+    events: I,
+    marker: String,
+    done: bool,
+}
+
+impl<I: Iterator<Item = Result<LogEvent, Error>>> Iterator for UntilMarker<I> {
+    type Item = Result<LogEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.events.next()?;
+        if let Ok(evt) = &next {
+            self.done = evt.has_marker(&self.marker);
+        }
+        Some(next)
+    }
+}
+
+/// Iterator over events sorted by timestamp within a window
+///
+/// See [`EventStreamExt::reorder`].
+pub struct Reorder<I> {
+    events: I,
+    window: i64,
+    /// Events not yet released, keyed by timestamp and the order they were read
+    pending: BTreeMap<(i64, u64), LogEvent>,
+    read: u64,
+    /// The latest timestamp read so far
+    latest: i64,
+    done: bool,
+}
+
+impl<I: Iterator<Item = Result<LogEvent, Error>>> Iterator for Reorder<I> {
+    type Item = Result<LogEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.pending.first_entry() {
+                if self.done || self.latest.saturating_sub(entry.key().0) > self.window {
+                    return Some(Ok(entry.remove()));
+                }
+            }
+            if self.done {
+                return None;
+            }
+            match self.events.next() {
+                Some(Ok(evt)) => {
+                    self.latest = self.latest.max(evt.time_stamp);
+                    self.pending.insert((evt.time_stamp, self.read), evt);
+                    self.read += 1;
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None => self.done = true,
+            }
+        }
+    }
+}
+
+#[test]
+fn test_reorder() {
+    use crate::LogLevel;
+    let events = [
+        ("b", 1_050),
+        ("a", 1_000),
+        ("c", 1_200),
+        ("d", 1_400),
+        ("late", 1_100),
+    ]
+    .map(|(message, time)| {
+        let mut evt = LogEvent::new(LogLevel::Info, "a.Main", message, &[]);
+        evt.time_stamp = time;
+        Ok(evt)
+    });
+    let messages = events
+        .into_iter()
+        .reorder(Duration::from_millis(100))
+        .map(|evt| evt.unwrap().message().into_owned())
+        .collect::<Vec<_>>();
+    // "a" and "b" are swapped back but "late" is more than the window behind
+    // "d" so it can't be put back in order and is released as soon as it's read
+    assert_eq!(messages, ["a", "b", "c", "late", "d"]);
+}
+
+/// An event with the time it was received
+#[derive(Debug)]
+pub struct Stamped<T> {
+    pub event: T,
+    pub received_at: OffsetDateTime,
+}
+
+impl Stamped<LogEvent> {
+    /// How long after the event was logged it was received. This is negative
+    /// if the clocks of the two machines don't agree.
+    pub fn latency(&self) -> time::Duration {
+        self.received_at - self.event.time()
+    }
+}
+
+/// Iterator over events stamped with the time they were read
+///
+/// See [`EventStreamExt::stamp_received`].
+pub struct StampReceived<I, C = SystemClock> {
+    events: I,
+    clock: C,
+}
+
+impl<I: Iterator<Item = Result<LogEvent, Error>>, C: Clock> Iterator for StampReceived<I, C> {
+    type Item = Result<Stamped<LogEvent>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let evt = self.events.next()?;
+        let received_at = self.clock.now();
+        Some(evt.map(|event| Stamped { event, received_at }))
+    }
+}
+
+#[test]
+fn test_stamp_received() {
+    use crate::LogLevel;
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    evt.time_stamp = 1_689_000_001_500;
+    let before = OffsetDateTime::now_utc();
+    let stamped = std::iter::once(Ok(evt))
+        .stamp_received()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert!(stamped.received_at >= before);
+    assert!(stamped.latency() > time::Duration::ZERO);
+    assert_eq!(
+        stamped.latency(),
+        stamped.received_at - stamped.event.time()
+    );
+}
+
+#[test]
+fn test_stamp_received_with() {
+    use crate::{LogLevel, TestClock};
+    let events = [1_000, 1_100].map(|time| {
+        let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+        evt.time_stamp = time;
+        Ok(evt)
+    });
+    let clock = TestClock::new(OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(2));
+    let mut stamped = events.into_iter().stamp_received_with(clock.clone());
+    let first = stamped.next().unwrap().unwrap();
+    assert_eq!(first.latency(), time::Duration::milliseconds(1_000));
+    clock.advance(time::Duration::milliseconds(50));
+    let second = stamped.next().unwrap().unwrap();
+    assert_eq!(second.latency(), time::Duration::milliseconds(950));
+}
+
+/// A break in the sequence numbers of consecutive events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceGap {
+    /// The number following the last event before the gap
+    pub expected: i64,
+    /// The number of the event after the gap. This is less than `expected` if
+    /// events were reordered or repeated.
+    pub found: i64,
+}
+
+impl SequenceGap {
+    /// The number of events missing from the gap, or zero if the event was
+    /// out of order
+    pub fn missing(&self) -> u64 {
+        self.found.saturating_sub(self.expected).max(0) as u64
+    }
+}
+
+/// Iterator over events paired with any gap in sequence numbers before them
+///
+/// See [`EventStreamExt::detect_gaps_by_sequence`].
+pub struct SequenceGaps<I> {
+    events: I,
+    last: Option<i64>,
+}
+
+impl<I: Iterator<Item = Result<LogEvent, Error>>> Iterator for SequenceGaps<I> {
+    type Item = Result<(LogEvent, Option<SequenceGap>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let evt = match self.events.next()? {
+            Ok(evt) => evt,
+            Err(e) => return Some(Err(e)),
+        };
+        let Some(found) = evt.sequence_number() else {
+            return Some(Ok((evt, None)));
+        };
+        let gap = self
+            .last
+            .replace(found)
+            .map(|last| SequenceGap {
+                expected: last.wrapping_add(1),
+                found,
+            })
+            .filter(|gap| gap.expected != gap.found);
+        Some(Ok((evt, gap)))
+    }
+}
+
+#[test]
+fn test_detect_gaps_by_sequence() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/sequence_gap.ser");
+    let gaps = crate::LogEventStream::new(data)
+        .unwrap()
+        .detect_gaps_by_sequence()
+        .map(|item| item.unwrap().1)
+        .collect::<Vec<_>>();
+    let gap = SequenceGap {
+        expected: 3,
+        found: 4,
+    };
+    assert_eq!(gaps, [None, None, Some(gap), None]);
+    assert_eq!(gap.missing(), 1);
+}
+
+#[test]
+fn test_sequence_gap_out_of_order() {
+    let gap = SequenceGap {
+        expected: 6,
+        found: 2,
+    };
+    assert_eq!(gap.missing(), 0);
+}
+
+#[test]
+fn test_until_marker() {
+    use crate::{LogLevel, Marker};
+    let events = ["loading", "other marker", "started", "running"].map(|message| {
+        let mut evt = LogEvent::new(LogLevel::Info, "a.Main", message, &[]);
+        evt.marker = match message {
+            "other marker" => Some(Marker::new("AUDIT", vec![])),
+            "started" => Some(Marker::new("STARTUP_COMPLETE", vec![])),
+            _ => None,
+        };
+        Ok(evt)
+    });
+    let messages = events
+        .into_iter()
+        .until_marker("STARTUP_COMPLETE")
+        .map(|evt| evt.unwrap().message().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["loading", "other marker", "started"]);
+}
+
+/// Iterator counting the events passing through it
+///
+/// See [`EventStreamExt::metered`].
+pub struct Metered<I> {
+    events: I,
+    meter: Meter,
+}
+
+impl<I> Metered<I> {
+    /// A handle to the count of events that can be shared with other threads
+    pub fn meter(&self) -> Meter {
+        self.meter.clone()
+    }
+}
+
+impl<I: Iterator<Item = Result<LogEvent, Error>>> Iterator for Metered<I> {
+    type Item = Result<LogEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.events.next()?;
+        if next.is_ok() {
+            self.meter.count.fetch_add(1, Ordering::Relaxed);
+        }
+        Some(next)
+    }
+}
+
+/// Shared count of the events read by a [`Metered`] stream. Errors are not
+/// counted.
+#[derive(Clone)]
+pub struct Meter {
+    count: Arc<AtomicU64>,
+    clock: Arc<dyn Clock + Send + Sync>,
+    /// The clock's [`elapsed`](Clock::elapsed) time when metering started
+    start: time::Duration,
+}
+
+impl std::fmt::Debug for Meter {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("Meter")
+            .field("count", &self.count)
+            .field("start", &self.start)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Meter {
+    /// The number of events read so far
+    pub fn total(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+    /// The average number of events read per second since the stream was
+    /// metered
+    ///
+    /// The time is measured with the clock's [`Clock::elapsed`] so, with the
+    /// default [`SystemClock`], changes to the system's time don't affect it.
+    pub fn rate_per_second(&self) -> f64 {
+        let elapsed = (self.clock.elapsed() - self.start).as_seconds_f64();
+        if elapsed > 0.0 {
+            self.total() as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+}
+
+#[test]
+fn test_metered() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let mut stream = crate::LogEventStream::new(data).unwrap().metered();
+    let meter = stream.meter();
+    assert_eq!(meter.total(), 0);
+    stream.next().unwrap().unwrap();
+    assert_eq!(meter.total(), 1);
+    assert_eq!(stream.count(), 4);
+    assert_eq!(meter.total(), 5);
+    assert!(meter.rate_per_second() > 0.0);
+}
+
+#[test]
+fn test_metered_with() {
+    let clock = crate::TestClock::default();
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let stream = crate::LogEventStream::new(data)
+        .unwrap()
+        .metered_with(clock.clone());
+    let meter = stream.meter();
+    assert_eq!(stream.count(), 5);
+    assert_eq!(meter.rate_per_second(), 0.0);
+    clock.advance(time::Duration::seconds(2));
+    assert_eq!(meter.rate_per_second(), 2.5);
+}
+
+#[cfg(test)]
+fn request(id: Option<&str>, message: &str) -> Result<LogEvent, Error> {
+    let mut evt = LogEvent::new(crate::LogLevel::Info, "a.Server", message, &[]);
+    if let Some(id) = id {
+        evt.mdc.insert("requestId".into(), id.into());
+    }
+    Ok(evt)
+}
+
+#[cfg(test)]
+fn summarise(group: Result<(Option<String>, Vec<LogEvent>), Error>) -> (Option<String>, usize) {
+    let (key, events) = group.unwrap();
+    (key, events.len())
+}
+
+#[cfg(test)]
+fn requests() -> Vec<Result<LogEvent, Error>> {
+    vec![
+        request(Some("a"), "start"),
+        request(Some("a"), "query"),
+        request(Some("b"), "start"),
+        request(None, "tick"),
+        request(Some("b"), "query"),
+        request(Some("a"), "done"),
+        request(Some("b"), "done"),
+    ]
+}
+
+#[test]
+fn test_group_by_mdc() {
+    let groups = requests()
+        .into_iter()
+        .group_by_mdc("requestId")
+        .map(summarise)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        groups,
+        [
+            (Some("a".into()), 2),
+            (Some("b".into()), 1),
+            (None, 1),
+            (Some("b".into()), 1),
+            (Some("a".into()), 1),
+            (Some("b".into()), 1),
+        ]
+    );
+
+    let groups = requests()
+        .into_iter()
+        .group_by_mdc("requestId")
+        .skip_missing()
+        .map(summarise)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        groups,
+        [
+            (Some("a".into()), 2),
+            (Some("b".into()), 2),
+            (Some("a".into()), 1),
+            (Some("b".into()), 1),
+        ]
+    );
+}
@@ -0,0 +1,124 @@
+use serde_json::{json, Map, Value};
+
+use crate::LogEvent;
+
+/// Fields Bunyan's core uses and the ones added from the event itself
+const RESERVED: &[&str] = &[
+    "v", "level", "name", "hostname", "pid", "time", "msg", "src", "err", "logger", "thread",
+    "args", "kv",
+];
+
+/// Convert an event into a record in the JSON format used by Bunyan, eg
+/// `{"v":0,"level":30,"name":"app","hostname":"ws001","time":"...","msg":"..."}`
+///
+/// The level is the [Bunyan level](crate::LogLevel::bunyan_level) and the time
+/// is in UTC with millisecond precision. The logger and thread are included as
+/// `logger` and `thread`, and a throwable as an `err` object with `name`,
+/// `message` and `stack` as Bunyan's error serializer writes them. Arguments
+/// are included as an `args` array and key/value pairs as a `kv` object, if
+/// there are any. MDC entries are added as fields of their own unless their
+/// key is one of these or one Bunyan reserves (`pid` and `src`), in which case
+/// they are left out, even if this event doesn't have that field. The process
+/// ID isn't known so `pid` is left out.
+pub fn to_bunyan(evt: &LogEvent, name: &str, hostname: &str) -> Value {
+    let mut record = Map::new();
+    record.insert("v".into(), 0.into());
+    record.insert("level".into(), evt.level.bunyan_level().into());
+    record.insert("name".into(), name.into());
+    record.insert("hostname".into(), hostname.into());
+    record.insert("logger".into(), evt.logger_name.to_string().into());
+    record.insert("thread".into(), evt.thread_name.as_str().into());
+    let time = evt.format_time("yyyy-MM-dd'T'HH:mm:ss.SSS'Z'").unwrap();
+    record.insert("time".into(), time.into());
+    record.insert("msg".into(), evt.message().into());
+    if let Some(t) = &evt.throwable {
+        let err = json!({
+            "name": t.class_name(),
+            "message": t.message().unwrap_or_default(),
+            "stack": t.format_chain(usize::MAX),
+        });
+        record.insert("err".into(), err);
+    }
+    if !evt.arguments().is_empty() {
+        record.insert("args".into(), evt.arguments().into());
+    }
+    if !evt.key_value_pairs().is_empty() {
+        let kv = evt
+            .key_value_pairs()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.as_str().into()))
+            .collect::<Map<_, _>>();
+        record.insert("kv".into(), kv.into());
+    }
+    for (key, value) in evt.mdc_sorted() {
+        if !RESERVED.contains(&key) {
+            record.insert(key.into(), value.into());
+        }
+    }
+    Value::Object(record)
+}
+
+#[test]
+fn test_bunyan() {
+    use crate::LogLevel;
+    let mut evt = LogEvent::new(LogLevel::Warn, "a.Main", "Disk {} full", &["/tmp"]);
+    evt.time_stamp = 1_689_000_001_500;
+    evt.mdc.insert("user".into(), "abc".into());
+    evt.mdc.insert("msg".into(), "ignored".into());
+    assert_eq!(
+        to_bunyan(&evt, "app", "ws001"),
+        json!({
+            "v": 0,
+            "level": 40,
+            "name": "app",
+            "hostname": "ws001",
+            "logger": "a.Main",
+            "thread": "main",
+            "time": "2023-07-10T14:40:01.500Z",
+            "msg": "Disk /tmp full",
+            "args": ["/tmp"],
+            "user": "abc",
+        })
+    );
+
+    evt.throwable = Some(crate::Throwable::new(
+        "java.io.IOException",
+        &[("a.Main", "run")],
+    ));
+    let err = &to_bunyan(&evt, "app", "ws001")["err"];
+    assert_eq!(err["name"], "java.io.IOException");
+    assert_eq!(err["message"], "");
+    assert_eq!(
+        err["stack"],
+        "java.io.IOException\n     at a.Main.run(Main.java:1)"
+    );
+
+    let levels = [
+        (LogLevel::Error, 50),
+        (LogLevel::Warn, 40),
+        (LogLevel::Info, 30),
+        (LogLevel::Debug, 20),
+        (LogLevel::Trace, 10),
+    ];
+    for (level, bunyan) in levels {
+        let evt = LogEvent::new(level, "a.Main", "", &[]);
+        assert_eq!(to_bunyan(&evt, "app", "ws001")["level"], bunyan);
+    }
+}
+
+#[test]
+fn test_bunyan_reserved_mdc() {
+    use crate::LogLevel;
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "Started", &[]);
+    for key in ["pid", "src", "err", "v", "args", "kv", "user"] {
+        evt.mdc.insert(key.into(), "mdc".into());
+    }
+    evt.key_value_pairs.push(("port".into(), "8080".into()));
+    let record = to_bunyan(&evt, "app", "ws001");
+    assert_eq!(record["v"], 0);
+    assert_eq!(record["kv"], json!({"port": "8080"}));
+    assert_eq!(record["user"], "mdc");
+    for key in ["pid", "src", "err", "args"] {
+        assert!(record.get(key).is_none(), "{key} should be left out");
+    }
+}
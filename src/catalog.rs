@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+
+use crate::{LogEvent, Source};
+
+/// Accumulator of the distinct logger names seen, with the number of events
+/// logged by each
+#[derive(Debug, Default)]
+pub struct LoggerCatalog {
+    counts: BTreeMap<String, usize>,
+}
+
+/// A package or logger in the tree built by [`LoggerCatalog::tree`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LoggerNode {
+    /// The number of events logged by the logger with exactly this name
+    pub count: usize,
+    /// The next part of the name for each logger below this one, eg `Main`
+    /// under `com.example`
+    pub children: BTreeMap<String, LoggerNode>,
+}
+
+impl LoggerNode {
+    /// The number of events logged by this logger and every logger below it
+    pub fn total(&self) -> usize {
+        self.count + self.children.values().map(LoggerNode::total).sum::<usize>()
+    }
+}
+
+impl LoggerCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, evt: &LogEvent) {
+        match self.counts.get_mut(&evt.logger_name.0) {
+            Some(count) => *count += 1,
+            None => _ = self.counts.insert(evt.logger_name.0.clone(), 1),
+        }
+    }
+
+    /// The logger names seen so far, sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.counts.keys().map(String::as_str)
+    }
+
+    /// The number of events recorded for a logger
+    pub fn count(&self, name: &str) -> usize {
+        self.counts.get(name).copied().unwrap_or(0)
+    }
+
+    /// The loggers seen grouped by package, eg `com.example.Main` is
+    /// `Main` under `example` under `com`. The root has no count of its own.
+    pub fn tree(&self) -> LoggerNode {
+        let mut root = LoggerNode::default();
+        for (name, count) in &self.counts {
+            let node = name.split('.').fold(&mut root, |node, part| {
+                node.children.entry(part.into()).or_default()
+            });
+            node.count += count;
+        }
+        root
+    }
+}
+
+/// The longest package prefix shared by every logger, eg `com.acme` for
+/// `com.acme.Main` and `com.acme.db.Pool`, so that loggers can be shown
+/// relative to it
+///
+/// Only whole parts of the names are compared so `com.acme` and `com.acmetools`
+/// share `com`. If there is nothing in common, or no loggers, the prefix is
+/// empty. A single logger is its own prefix.
+pub fn common_logger_prefix<'a>(mut loggers: impl Iterator<Item = &'a Source>) -> String {
+    let Some(first) = loggers.next() else {
+        return String::new();
+    };
+    let mut common = first.0.split('.').collect::<Vec<_>>();
+    for logger in loggers {
+        let shared = common
+            .iter()
+            .zip(logger.0.split('.'))
+            .take_while(|(a, b)| *a == b)
+            .count();
+        common.truncate(shared);
+    }
+    common.join(".")
+}
+
+#[test]
+fn test_common_logger_prefix() {
+    let loggers = [
+        "com.acme.orders.Service",
+        "com.acme.orders.db.Pool",
+        "com.acme.Main",
+    ]
+    .map(|name| Source(name.into()));
+    assert_eq!(common_logger_prefix(loggers.iter()), "com.acme");
+    assert_eq!(common_logger_prefix(loggers[..2].iter()), "com.acme.orders");
+
+    let other = [
+        Source("com.acmetools.Tool".into()),
+        Source("org.lib.Client".into()),
+    ];
+    assert_eq!(
+        common_logger_prefix(loggers.iter().chain(&other[..1])),
+        "com"
+    );
+    assert_eq!(common_logger_prefix(loggers.iter().chain(&other)), "");
+    assert_eq!(common_logger_prefix([].iter()), "");
+}
+
+#[test]
+fn test_logger_catalog() {
+    use crate::LogLevel;
+    let mut catalog = LoggerCatalog::new();
+    for logger in [
+        "com.example.Service",
+        "com.example.db.Pool",
+        "com.example.Service",
+        "org.lib.Client",
+        "com.example",
+    ] {
+        catalog.record(&LogEvent::new(LogLevel::Info, logger, "", &[]));
+    }
+    assert_eq!(
+        catalog.names().collect::<Vec<_>>(),
+        [
+            "com.example",
+            "com.example.Service",
+            "com.example.db.Pool",
+            "org.lib.Client"
+        ]
+    );
+    assert_eq!(catalog.count("com.example.Service"), 2);
+    assert_eq!(catalog.count("com.example.Missing"), 0);
+
+    let tree = catalog.tree();
+    assert_eq!(tree.total(), 5);
+    let example = &tree.children["com"].children["example"];
+    assert_eq!(example.count, 1);
+    assert_eq!(example.total(), 4);
+    assert_eq!(
+        example.children.keys().collect::<Vec<_>>(),
+        ["Service", "db"]
+    );
+    assert_eq!(example.children["db"].children["Pool"].count, 1);
+}
@@ -0,0 +1,95 @@
+use std::{
+    sync::{Arc, Mutex, OnceLock},
+    time::Instant,
+};
+
+use time::{Duration, OffsetDateTime};
+
+/// A source of the current time for adapters that need it, so that they can
+/// be tested without depending on the system clock
+pub trait Clock {
+    fn now(&self) -> OffsetDateTime;
+    /// The time since a fixed point, for measuring how long something took
+    ///
+    /// Unlike [`Clock::now`], this should not jump if the system's time is
+    /// changed. By default it is the time since the unix epoch.
+    fn elapsed(&self) -> Duration {
+        self.now() - OffsetDateTime::UNIX_EPOCH
+    }
+}
+
+/// The system's clock, in UTC, with a monotonic [`Clock::elapsed`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+    /// The time since this was first called in this process, measured with
+    /// [`Instant`]
+    fn elapsed(&self) -> Duration {
+        static START: OnceLock<Instant> = OnceLock::new();
+        let since = START.get_or_init(Instant::now).elapsed();
+        Duration::try_from(since).unwrap_or(Duration::MAX)
+    }
+}
+
+/// A clock that only changes when it is set or advanced
+///
+/// Clones share the same time so a clone can be given to an adapter and the
+/// original advanced from a test.
+#[derive(Debug, Clone)]
+pub struct TestClock {
+    now: Arc<Mutex<OffsetDateTime>>,
+}
+
+impl TestClock {
+    pub fn new(start: OffsetDateTime) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+    /// Move the clock on by the given duration (or back if it is negative)
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+    pub fn set(&self, now: OffsetDateTime) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Default for TestClock {
+    /// A clock stopped at the unix epoch
+    fn default() -> Self {
+        Self::new(OffsetDateTime::UNIX_EPOCH)
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> OffsetDateTime {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[test]
+fn test_test_clock() {
+    let clock = TestClock::default();
+    let shared = clock.clone();
+    assert_eq!(shared.now(), OffsetDateTime::UNIX_EPOCH);
+    clock.advance(Duration::milliseconds(1_500));
+    assert_eq!(shared.now().unix_timestamp_nanos(), 1_500_000_000);
+    clock.set(OffsetDateTime::UNIX_EPOCH + Duration::days(1));
+    assert_eq!(shared.now().unix_timestamp(), 86_400);
+}
+
+#[test]
+fn test_elapsed() {
+    let clock = TestClock::default();
+    assert_eq!(clock.elapsed(), Duration::ZERO);
+    clock.advance(Duration::seconds(3));
+    assert_eq!(clock.elapsed(), Duration::seconds(3));
+
+    let first = SystemClock.elapsed();
+    assert!(SystemClock.elapsed() >= first);
+}
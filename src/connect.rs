@@ -0,0 +1,110 @@
+use std::{
+    io,
+    net::{TcpStream, ToSocketAddrs},
+    thread,
+    time::Duration,
+};
+
+#[cfg(unix)]
+use std::{os::unix::net::UnixStream, path::Path};
+
+use crate::{Error, LogEventStream};
+
+/// How long to wait between attempts to connect to a server and how many
+/// attempts to make before giving up
+///
+/// The default tries every 200ms until the server is available.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    pub interval: Duration,
+    /// The maximum number of attempts or `None` to keep trying indefinitely
+    pub attempts: Option<u32>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(200),
+            attempts: None,
+        }
+    }
+}
+
+impl RetryConfig {
+    fn retry<T>(&self, mut connect: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut attempt = 1;
+        loop {
+            match connect() {
+                Ok(conn) => break Ok(conn),
+                Err(e) if self.attempts.is_some_and(|max| attempt >= max) => break Err(e),
+                Err(_) => thread::sleep(self.interval),
+            }
+            attempt += 1;
+        }
+    }
+}
+
+/// Connect to the ServerSocketAppender at the given address, retrying until the
+/// server is available
+pub fn connect_tcp<A: ToSocketAddrs>(
+    addr: A,
+    retry: &RetryConfig,
+) -> Result<LogEventStream<TcpStream>, Error> {
+    let sock = retry.retry(|| TcpStream::connect(&addr))?;
+    LogEventStream::new(sock)
+}
+
+/// Connect to a server publishing events on a Unix domain socket, retrying
+/// until the server is available
+#[cfg(unix)]
+pub fn connect_unix<P: AsRef<Path>>(
+    path: P,
+    retry: &RetryConfig,
+) -> Result<LogEventStream<UnixStream>, Error> {
+    let sock = retry.retry(|| UnixStream::connect(&path))?;
+    LogEventStream::new(sock)
+}
+
+#[test]
+fn test_retry_config_default() {
+    let retry = RetryConfig {
+        attempts: Some(3),
+        ..Default::default()
+    };
+    assert_eq!(retry.interval, Duration::from_millis(200));
+    assert_eq!(retry.attempts, Some(3));
+    assert_eq!(RetryConfig::default().attempts, None);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_connect_unix() {
+    use std::{io::Write, os::unix::net::UnixListener};
+
+    let path = std::env::temp_dir().join(format!("logback-test-{}.sock", std::process::id()));
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = thread::spawn(move || {
+        let (mut sock, _) = listener.accept().unwrap();
+        sock.write_all(include_bytes!("../tests/fixtures/five_events.ser"))
+            .unwrap();
+    });
+    let retry = RetryConfig {
+        attempts: Some(10),
+        ..Default::default()
+    };
+    let events = connect_unix(&path, &retry).unwrap().count();
+    server.join().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(events, 5);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_connect_unix_gives_up() {
+    let path = std::env::temp_dir().join("logback-test-missing.sock");
+    let retry = RetryConfig {
+        interval: Duration::from_millis(1),
+        attempts: Some(3),
+    };
+    assert!(matches!(connect_unix(path, &retry), Err(Error::Io(_))));
+}
@@ -0,0 +1,75 @@
+/// The distributed tracing IDs of an event, read from its MDC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Correlation {
+    pub trace_id: String,
+    pub span_id: Option<String>,
+}
+
+/// The MDC keys to look for trace and span IDs under, in order of preference
+///
+/// The defaults cover the keys used by OpenTelemetry (`trace_id`), Spring
+/// Cloud Sleuth/Micrometer (`traceId`), Zipkin's B3 propagation
+/// (`X-B3-TraceId`) and Datadog (`dd.trace_id`), with the matching span keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorrelationKeys {
+    pub trace: Vec<String>,
+    pub span: Vec<String>,
+}
+
+impl Default for CorrelationKeys {
+    fn default() -> Self {
+        let keys = |keys: [&str; 4]| keys.map(String::from).to_vec();
+        Self {
+            trace: keys(["traceId", "trace_id", "X-B3-TraceId", "dd.trace_id"]),
+            span: keys(["spanId", "span_id", "X-B3-SpanId", "dd.span_id"]),
+        }
+    }
+}
+
+#[cfg(test)]
+fn with_mdc(entries: &[(&str, &str)]) -> crate::LogEvent {
+    let mut evt = crate::LogEvent::new(crate::LogLevel::Info, "a.Main", "", &[]);
+    for (k, v) in entries {
+        evt.mdc.insert(k.to_string(), v.to_string());
+    }
+    evt
+}
+
+#[test]
+fn test_correlation_default_keys() {
+    for (trace, span) in [
+        ("traceId", "spanId"),
+        ("trace_id", "span_id"),
+        ("X-B3-TraceId", "X-B3-SpanId"),
+        ("dd.trace_id", "dd.span_id"),
+    ] {
+        let evt = with_mdc(&[(trace, "t1"), (span, "s1")]);
+        assert_eq!(
+            evt.correlation(),
+            Some(Correlation {
+                trace_id: "t1".into(),
+                span_id: Some("s1".into())
+            })
+        );
+    }
+    let trace_only = with_mdc(&[("trace_id", "t1")]).correlation().unwrap();
+    assert_eq!(trace_only.span_id, None);
+}
+
+#[test]
+fn test_correlation_absent() {
+    assert_eq!(with_mdc(&[]).correlation(), None);
+    assert_eq!(with_mdc(&[("spanId", "s1")]).correlation(), None);
+    assert_eq!(with_mdc(&[("traceId", "")]).correlation(), None);
+}
+
+#[test]
+fn test_correlation_custom_keys() {
+    let keys = CorrelationKeys {
+        trace: vec!["requestTrace".into()],
+        span: vec![],
+    };
+    let evt = with_mdc(&[("requestTrace", "r1"), ("traceId", "t1")]);
+    assert_eq!(evt.correlation_with(&keys).unwrap().trace_id, "r1");
+    assert_eq!(evt.correlation().unwrap().trace_id, "t1");
+}
@@ -0,0 +1,168 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+    str::FromStr,
+};
+
+use crate::{LogEvent, ThrowableRenderer};
+
+/// A value of an event that can be written as a CSV column
+///
+/// Columns are parsed from the names `timestamp`, `level`, `logger`, `thread`,
+/// `message`, `exception` and `mdc.<key>`, which are also used for the header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// The time of the event, eg `2023-07-10 14:40:01.500`
+    Timestamp,
+    Level,
+    Logger,
+    Thread,
+    Message,
+    /// The full trace of the event's throwable, including its causes and
+    /// suppressed throwables, or empty if there isn't one
+    Exception,
+    /// The value of an MDC entry, or empty if the event doesn't have it
+    Mdc(String),
+}
+
+impl CsvColumn {
+    /// Parse a comma separated list of column names, eg
+    /// `timestamp,level,mdc.user,message`
+    pub fn parse_list(columns: &str) -> Result<Vec<CsvColumn>, UnknownColumn> {
+        columns.split(',').map(|col| col.trim().parse()).collect()
+    }
+
+    fn value(&self, evt: &LogEvent) -> String {
+        match self {
+            Self::Timestamp => evt.format_time("yyyy-MM-dd HH:mm:ss.SSS").unwrap(),
+            Self::Level => evt.level.to_string(),
+            Self::Logger => evt.logger_name.to_string(),
+            Self::Thread => evt.thread_name.clone(),
+            Self::Message => evt.message().into_owned(),
+            Self::Exception => evt
+                .throwable
+                .as_ref()
+                .map(|ex| ThrowableRenderer::new().render(ex))
+                .unwrap_or_default(),
+            Self::Mdc(key) => evt.mdc.get(key).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+impl FromStr for CsvColumn {
+    type Err = UnknownColumn;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "timestamp" => Self::Timestamp,
+            "level" => Self::Level,
+            "logger" => Self::Logger,
+            "thread" => Self::Thread,
+            "message" => Self::Message,
+            "exception" => Self::Exception,
+            _ => match s.strip_prefix("mdc.") {
+                Some(key) if !key.is_empty() => Self::Mdc(key.into()),
+                _ => return Err(UnknownColumn(s.into())),
+            },
+        })
+    }
+}
+
+impl Display for CsvColumn {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Timestamp => fmt.write_str("timestamp"),
+            Self::Level => fmt.write_str("level"),
+            Self::Logger => fmt.write_str("logger"),
+            Self::Thread => fmt.write_str("thread"),
+            Self::Message => fmt.write_str("message"),
+            Self::Exception => fmt.write_str("exception"),
+            Self::Mdc(key) => write!(fmt, "mdc.{key}"),
+        }
+    }
+}
+
+/// A column name that isn't one of those accepted by [`CsvColumn`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownColumn(pub String);
+
+impl Display for UnknownColumn {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "Unknown column: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownColumn {}
+
+/// Write a header record with the name of each column
+pub fn write_csv_header<W: io::Write>(
+    writer: &mut csv::Writer<W>,
+    columns: &[CsvColumn],
+) -> csv::Result<()> {
+    writer.write_record(columns.iter().map(CsvColumn::to_string))
+}
+
+/// Write the given columns of an event as a single record
+///
+/// Values containing commas, quotes or line breaks (eg multi-line messages
+/// and exceptions) are quoted.
+pub fn write_csv_row<W: io::Write>(
+    writer: &mut csv::Writer<W>,
+    evt: &LogEvent,
+    columns: &[CsvColumn],
+) -> csv::Result<()> {
+    writer.write_record(columns.iter().map(|col| col.value(evt)))
+}
+
+#[test]
+fn test_write_csv() {
+    use crate::LogLevel;
+    let columns = CsvColumn::parse_list("timestamp,level,logger,mdc.user,message").unwrap();
+    let mut first = LogEvent::new(LogLevel::Info, "a.Main", "Hello, {}", &["world"]);
+    first.mdc.insert("user".into(), "abc".into());
+    let second = LogEvent::new(LogLevel::Warn, "a.Main", "Said \"{}\"\nthen left", &["hi"]);
+
+    let mut writer = csv::Writer::from_writer(vec![]);
+    write_csv_header(&mut writer, &columns).unwrap();
+    write_csv_row(&mut writer, &first, &columns).unwrap();
+    write_csv_row(&mut writer, &second, &columns).unwrap();
+    let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(
+        csv,
+        "timestamp,level,logger,mdc.user,message\n\
+         1970-01-01 00:00:00.000,INFO,a.Main,abc,\"Hello, world\"\n\
+         1970-01-01 00:00:00.000,WARN,a.Main,,\"Said \"\"hi\"\"\nthen left\"\n"
+    );
+}
+
+#[test]
+fn test_write_csv_exception() {
+    use crate::{LogLevel, Throwable};
+    let mut evt = LogEvent::new(LogLevel::Error, "a.Main", "Failed", &[]);
+    evt.throwable = Some(
+        Throwable::new("a.StartupException", &[]).caused_by(Throwable::new("a.DiskFull", &[])),
+    );
+    let mut writer = csv::Writer::from_writer(vec![]);
+    write_csv_row(&mut writer, &evt, &[CsvColumn::Exception]).unwrap();
+    let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(csv, "\"a.StartupException\nCaused by: a.DiskFull\"\n");
+}
+
+#[test]
+fn test_parse_columns() {
+    assert_eq!(
+        CsvColumn::parse_list("level, exception,mdc.a.b"),
+        Ok(vec![
+            CsvColumn::Level,
+            CsvColumn::Exception,
+            CsvColumn::Mdc("a.b".into())
+        ])
+    );
+    assert_eq!(
+        CsvColumn::parse_list("level,mdc."),
+        Err(UnknownColumn("mdc.".into()))
+    );
+    assert_eq!(
+        "time".parse::<CsvColumn>(),
+        Err(UnknownColumn("time".into()))
+    );
+}
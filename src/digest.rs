@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+use crate::LogEvent;
+
+/// Summary of the events sharing a fingerprint
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestEntry {
+    pub fingerprint: u64,
+    pub first_seen: OffsetDateTime,
+    pub last_seen: OffsetDateTime,
+    pub count: usize,
+    /// The message of the first event recorded
+    pub sample: String,
+}
+
+/// Accumulator of when and how often each kind of event has been seen, keyed
+/// by [`LogEvent::fingerprint`]
+#[derive(Debug, Default)]
+pub struct Digest {
+    entries: HashMap<u64, DigestEntry>,
+}
+
+impl Digest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, evt: &LogEvent) {
+        let fingerprint = evt.fingerprint();
+        let time = evt.time();
+        self.entries
+            .entry(fingerprint)
+            .and_modify(|entry| {
+                entry.first_seen = entry.first_seen.min(time);
+                entry.last_seen = entry.last_seen.max(time);
+                entry.count += 1;
+            })
+            .or_insert_with(|| DigestEntry {
+                fingerprint,
+                first_seen: time,
+                last_seen: time,
+                count: 1,
+                sample: evt.message().into_owned(),
+            });
+    }
+
+    /// The entries recorded so far, most common first. Entries seen the same
+    /// number of times are in the order they were first seen.
+    pub fn entries(&self) -> impl Iterator<Item = &DigestEntry> {
+        let mut entries = self.entries.values().collect::<Vec<_>>();
+        entries.sort_by_key(|e| (std::cmp::Reverse(e.count), e.first_seen));
+        entries.into_iter()
+    }
+}
+
+#[test]
+fn test_digest() {
+    use crate::LogLevel;
+    let event = |template: &str, arg: &str, time: i64| {
+        let mut evt = LogEvent::new(LogLevel::Error, "a.Service", template, &[arg]);
+        evt.time_stamp = time;
+        evt
+    };
+    let mut digest = Digest::new();
+    digest.record(&event("Connection to {} lost", "db1", 1_000));
+    digest.record(&event("User {} not found", "abc", 2_000));
+    digest.record(&event("User {} not found", "def", 5_000));
+    digest.record(&event("User {} not found", "ghi", 3_000));
+
+    let entries = digest.entries().collect::<Vec<_>>();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].count, 3);
+    assert_eq!(entries[0].sample, "User abc not found");
+    assert_eq!(entries[0].first_seen.unix_timestamp(), 2);
+    assert_eq!(entries[0].last_seen.unix_timestamp(), 5);
+    assert_eq!(entries[1].count, 1);
+    assert_eq!(entries[1].sample, "Connection to db1 lost");
+    assert_eq!(entries[1].first_seen, entries[1].last_seen);
+}
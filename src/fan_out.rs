@@ -0,0 +1,171 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+};
+
+use crate::{Error, LogEvent};
+
+/// What to do when a receiver's queue is full
+///
+/// The default blocks so that no events are lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backpressure {
+    /// Wait for the receiver to catch up before reading any more events
+    #[default]
+    Block,
+    /// Drop the oldest event the receiver hasn't read yet
+    DropOldest,
+}
+
+/// Read events on a new thread and send each one to several receivers
+///
+/// Each receiver has its own queue of up to `capacity` events and `policy`
+/// controls what happens when one is full. Receivers that are dropped no
+/// longer hold up the others. The thread ends at the end of the stream or at
+/// the first error, which is returned from the handle.
+pub fn fan_out<I>(
+    mut events: I,
+    receivers: usize,
+    capacity: usize,
+    policy: Backpressure,
+) -> (Vec<FanOutReceiver>, JoinHandle<Result<(), Error>>)
+where
+    I: Iterator<Item = Result<LogEvent, Error>> + Send + 'static,
+{
+    let queues = (0..receivers)
+        .map(|_| Arc::new(Queue::default()))
+        .collect::<Vec<_>>();
+    let senders = queues.clone();
+    let handle = thread::spawn(move || {
+        let result = events.try_for_each(|evt| {
+            let evt = Arc::new(evt?);
+            for queue in &senders {
+                queue.push(evt.clone(), capacity.max(1), policy);
+            }
+            Ok(())
+        });
+        for queue in &senders {
+            queue.lock().closed = true;
+            queue.changed.notify_all();
+        }
+        result
+    });
+    let receivers = queues
+        .into_iter()
+        .map(|queue| FanOutReceiver { queue })
+        .collect();
+    (receivers, handle)
+}
+
+/// One of the consumers of a [`fan_out`]
+///
+/// Iterating blocks until the next event is available and ends once the
+/// reading thread has finished and every event sent has been read.
+pub struct FanOutReceiver {
+    queue: Arc<Queue>,
+}
+
+impl Iterator for FanOutReceiver {
+    type Item = Arc<LogEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut state = self.queue.lock();
+        loop {
+            if let Some(evt) = state.events.pop_front() {
+                self.queue.changed.notify_all();
+                return Some(evt);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self.queue.changed.wait(state).unwrap();
+        }
+    }
+}
+
+impl Drop for FanOutReceiver {
+    fn drop(&mut self) {
+        let mut state = self.queue.lock();
+        state.dropped = true;
+        state.events.clear();
+        self.queue.changed.notify_all();
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    state: Mutex<QueueState>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct QueueState {
+    events: VecDeque<Arc<LogEvent>>,
+    /// Set once no more events will be sent
+    closed: bool,
+    /// Set once the receiver has been dropped and events no longer need to be
+    /// sent to it
+    dropped: bool,
+}
+
+impl Queue {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap()
+    }
+
+    fn push(&self, evt: Arc<LogEvent>, capacity: usize, policy: Backpressure) {
+        let mut state = self.lock();
+        while !state.dropped && state.events.len() >= capacity {
+            match policy {
+                Backpressure::Block => state = self.changed.wait(state).unwrap(),
+                Backpressure::DropOldest => {
+                    state.events.pop_front();
+                }
+            }
+        }
+        if !state.dropped {
+            state.events.push_back(evt);
+            self.changed.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+fn five_events() -> crate::LogEventStream<&'static [u8]> {
+    crate::LogEventStream::new(&include_bytes!("../tests/fixtures/five_events.ser")[..]).unwrap()
+}
+
+#[test]
+fn test_fan_out() {
+    use crate::LogLevel;
+    let (mut receivers, handle) = fan_out(five_events(), 2, 2, Backpressure::Block);
+    let counter = receivers.pop().unwrap();
+    let errors = receivers.pop().unwrap();
+    let counter = thread::spawn(move || counter.count());
+    let errors = errors
+        .filter(|evt| evt.level == LogLevel::Error)
+        .map(|evt| evt.message().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(errors, ["event 5"]);
+    assert_eq!(counter.join().unwrap(), 5);
+    assert!(handle.join().unwrap().is_ok());
+}
+
+#[test]
+fn test_fan_out_drop_oldest() {
+    let (mut receivers, handle) = fan_out(five_events(), 2, 1, Backpressure::DropOldest);
+    drop(receivers.pop());
+    assert!(handle.join().unwrap().is_ok());
+    let messages = receivers
+        .pop()
+        .unwrap()
+        .map(|evt| evt.message().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["event 5"]);
+}
+
+#[test]
+fn test_backpressure_default() {
+    assert_eq!(Backpressure::default(), Backpressure::Block);
+}
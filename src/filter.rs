@@ -0,0 +1,189 @@
+use crate::{LogEvent, LogLevel};
+
+/// Criteria for selecting which events to keep from a stream
+///
+/// The default filter accepts every event.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    min_level: Option<LogLevel>,
+    exclude_unknown: bool,
+    trace_id: Option<String>,
+    #[cfg(feature = "regex")]
+    message: Option<regex::Regex>,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accept events at or above the given level
+    pub fn min_level(mut self, level: LogLevel) -> Self {
+        self.min_level = Some(level);
+        self
+    }
+
+    /// Whether events with a level that isn't one of the standard logback
+    /// levels should be accepted when a minimum level is set
+    ///
+    /// By default they are accepted, matching the ordering of [`LogLevel`]
+    /// where `Unknown` is above `Error` and the threshold used by
+    /// [`LogEventStream::with_min_level`](crate::LogEventStream::with_min_level)
+    /// and [`run_tail`](crate::run_tail). Without a minimum level they are
+    /// accepted either way.
+    pub fn include_unknown(mut self, include: bool) -> Self {
+        self.exclude_unknown = !include;
+        self
+    }
+
+    /// Only accept events with the given trace ID (see
+    /// [`LogEvent::correlation`]), eg to follow a single request
+    pub fn correlation_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    /// Only accept events whose message (with arguments substituted) matches
+    /// the given regex
+    ///
+    /// The pattern is compiled here so an invalid pattern is reported before
+    /// any events are read.
+    #[cfg(feature = "regex")]
+    pub fn message_regex(self, pattern: &str) -> Result<Self, regex::Error> {
+        self.build_message_regex(pattern, false)
+    }
+
+    /// As [`Filter::message_regex`] but ignoring case
+    #[cfg(feature = "regex")]
+    pub fn message_regex_ignore_case(self, pattern: &str) -> Result<Self, regex::Error> {
+        self.build_message_regex(pattern, true)
+    }
+
+    #[cfg(feature = "regex")]
+    fn build_message_regex(
+        mut self,
+        pattern: &str,
+        ignore_case: bool,
+    ) -> Result<Self, regex::Error> {
+        self.message = Some(
+            regex::RegexBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .build()?,
+        );
+        Ok(self)
+    }
+
+    #[cfg(feature = "regex")]
+    fn message_matches(&self, event: &LogEvent) -> bool {
+        self.message
+            .as_ref()
+            .is_none_or(|re| re.is_match(&event.message()))
+    }
+
+    #[cfg(not(feature = "regex"))]
+    fn message_matches(&self, _: &LogEvent) -> bool {
+        true
+    }
+
+    /// Check whether an event meets all the criteria of this filter
+    pub fn matches(&self, event: &LogEvent) -> bool {
+        let level = match (self.min_level, event.level) {
+            (None, _) => true,
+            (Some(_), LogLevel::Unknown) => !self.exclude_unknown,
+            (Some(min), level) => level >= min,
+        };
+        level
+            && self
+                .trace_id
+                .as_ref()
+                .is_none_or(|id| event.correlation().is_some_and(|c| &c.trace_id == id))
+            && self.message_matches(event)
+    }
+}
+
+#[test]
+fn test_min_level() {
+    let filter = Filter::new().min_level(LogLevel::Warn);
+    assert!(!filter.matches(&LogEvent::new(LogLevel::Info, "a.Main", "info", &[])));
+    assert!(filter.matches(&LogEvent::new(LogLevel::Warn, "a.Main", "warn", &[])));
+    assert!(filter.matches(&LogEvent::new(LogLevel::Error, "a.Main", "error", &[])));
+}
+
+#[test]
+fn test_include_unknown() {
+    let evt = LogEvent::new(LogLevel::Unknown, "a.Main", "custom level", &[]);
+    assert!(Filter::new().matches(&evt));
+
+    let filter = Filter::new().min_level(LogLevel::Error);
+    assert!(filter.matches(&evt));
+    assert!(!filter.clone().include_unknown(false).matches(&evt));
+    assert!(filter.include_unknown(true).matches(&evt));
+}
+
+#[test]
+fn test_correlation_id() {
+    let events = [
+        ("a", "traceId"),
+        ("b", "traceId"),
+        ("a", "trace_id"),
+        ("", ""),
+    ]
+    .map(|(trace, key)| {
+        let mut evt = LogEvent::new(LogLevel::Info, "a.Main", trace, &[]);
+        if !key.is_empty() {
+            evt.mdc.insert(key.into(), trace.into());
+        }
+        evt
+    });
+    let filter = Filter::new().correlation_id("a");
+    let kept = events.iter().filter(|evt| filter.matches(evt)).count();
+    assert_eq!(kept, 2);
+    let filter = filter.min_level(LogLevel::Warn);
+    assert!(!events.iter().any(|evt| filter.matches(evt)));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_message_regex() {
+    let evt = LogEvent::new(
+        LogLevel::Info,
+        "a.Main",
+        "Connected to {} in {}ms",
+        &["db-1", "42"],
+    );
+    let filter = Filter::new().message_regex(r"db-\d+ in \d+ms").unwrap();
+    assert!(filter.matches(&evt));
+    assert!(!Filter::new()
+        .message_regex("^Disconnected")
+        .unwrap()
+        .matches(&evt));
+
+    assert!(!Filter::new()
+        .message_regex("connected TO")
+        .unwrap()
+        .matches(&evt));
+    let filter = Filter::new()
+        .message_regex_ignore_case("connected TO")
+        .unwrap();
+    assert!(filter.matches(&evt));
+    assert!(!filter.min_level(LogLevel::Warn).matches(&evt));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_message_regex_invalid() {
+    assert!(Filter::new().message_regex("unclosed (group").is_err());
+}
+
+#[test]
+fn test_filter_default() {
+    let filter = Filter {
+        min_level: Some(LogLevel::Warn),
+        ..Default::default()
+    };
+    assert!(!filter.exclude_unknown);
+    assert!(filter.trace_id.is_none());
+    #[cfg(feature = "regex")]
+    assert!(filter.message.is_none());
+    assert!(Filter::default().min_level.is_none());
+}
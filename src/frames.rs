@@ -0,0 +1,72 @@
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+use crate::LogEvent;
+
+/// One JSON object for each frame of an event's throwable and its causes
+///
+/// Each object has the `event_id` it was given, the `frame_index` within its
+/// throwable, the `class`, `method`, `file` and `line` of the frame and the
+/// `cause_depth` of its throwable (0 for the event's throwable, 1 for its cause
+/// etc). Suppressed throwables are not included.
+pub fn frame_records(evt: &LogEvent, event_id: &str) -> Vec<Value> {
+    evt.throwable
+        .iter()
+        .flat_map(|t| t.chain().enumerate())
+        .flat_map(|(depth, t)| {
+            t.stack_trace.iter().enumerate().map(move |(index, ste)| {
+                json!({
+                    "event_id": event_id,
+                    "frame_index": index,
+                    "class": ste.ste.class_name(),
+                    "method": ste.ste.method_name(),
+                    "file": ste.ste.file_name(),
+                    "line": ste.ste.line(),
+                    "cause_depth": depth,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Write the [`frame_records`] of an event as newline delimited JSON,
+/// returning the number of lines written
+pub fn write_frames<W: Write>(out: &mut W, evt: &LogEvent, event_id: &str) -> io::Result<usize> {
+    let records = frame_records(evt, event_id);
+    for record in &records {
+        serde_json::to_writer(&mut *out, record)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(records.len())
+}
+
+#[test]
+fn test_write_frames() {
+    let evt = crate::read_event(include_bytes!("../tests/fixtures/exception.ser"));
+    let mut out = vec![];
+    let count = write_frames(&mut out, &evt, "evt-1").unwrap();
+    let total = evt.throwable.as_ref().unwrap().total_frames();
+    assert_eq!(count, total);
+
+    let lines = String::from_utf8(out).unwrap();
+    let records = lines
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(records.len(), total);
+    assert_eq!(
+        records[3],
+        json!({
+            "event_id": "evt-1",
+            "frame_index": 0,
+            "class": "com.example.io.Reader",
+            "method": "read",
+            "file": "Reader.java",
+            "line": 91,
+            "cause_depth": 1,
+        })
+    );
+
+    let plain = LogEvent::new(crate::LogLevel::Info, "a.Main", "", &[]);
+    assert_eq!(write_frames(&mut vec![], &plain, "evt-2").unwrap(), 0);
+}
@@ -0,0 +1,82 @@
+use serde_json::{Map, Value};
+
+use crate::LogEvent;
+
+/// Convert an event into a Graylog Extended Log Format (GELF) message
+///
+/// The message is used as the `short_message` and, if the event has a
+/// throwable, the message followed by the full trace is the `full_message`.
+/// Each argument is included as an additional field, `_arg_0`, `_arg_1` etc, so
+/// that arguments without an anchor in the message aren't lost. Each MDC entry
+/// is also included with a `_` prefixed to its key, apart from `id` which GELF
+/// does not allow. MDC entries replace arguments with the same name.
+pub fn to_gelf(evt: &LogEvent, host: &str) -> Value {
+    let mut gelf = Map::new();
+    gelf.insert("version".into(), "1.1".into());
+    gelf.insert("host".into(), host.into());
+    gelf.insert("short_message".into(), evt.message().into());
+    if let Some(t) = &evt.throwable {
+        let full = format!("{}\n{}", evt.message(), t.format_chain(usize::MAX));
+        gelf.insert("full_message".into(), full.into());
+    }
+    gelf.insert("timestamp".into(), (evt.time_stamp as f64 / 1000.0).into());
+    gelf.insert("level".into(), evt.level.syslog_severity().into());
+    for (i, arg) in evt.arguments().iter().enumerate() {
+        gelf.insert(format!("_arg_{i}"), arg.as_str().into());
+    }
+    for (key, value) in evt.mdc_sorted() {
+        if key != "id" {
+            gelf.insert(format!("_{key}"), value.into());
+        }
+    }
+    Value::Object(gelf)
+}
+
+#[test]
+fn test_gelf() {
+    use crate::LogLevel;
+    use serde_json::json;
+    let mut evt = LogEvent::new(LogLevel::Warn, "a.Main", "Disk {} full", &["/tmp"]);
+    evt.time_stamp = 1_689_000_001_500;
+    evt.mdc.insert("user".into(), "abc".into());
+    evt.mdc.insert("id".into(), "42".into());
+    assert_eq!(
+        to_gelf(&evt, "ws001"),
+        json!({
+            "version": "1.1",
+            "host": "ws001",
+            "short_message": "Disk /tmp full",
+            "timestamp": 1_689_000_001.5,
+            "level": 4,
+            "_arg_0": "/tmp",
+            "_user": "abc",
+        })
+    );
+
+    evt.throwable = Some(crate::Throwable::new("java.io.IOException", &[]));
+    let gelf = to_gelf(&evt, "ws001");
+    assert_eq!(gelf["full_message"], "Disk /tmp full\njava.io.IOException");
+    // New lines are always escaped in JSON so each message stays on one line
+    let json = serde_json::to_string(&gelf).unwrap();
+    assert!(json.contains(r"full\njava.io") && !json.contains('\n'));
+
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "Loaded {}", &["config", "3"]);
+    let gelf = to_gelf(&evt, "ws001");
+    assert_eq!(gelf["short_message"], "Loaded config");
+    assert_eq!(
+        (&gelf["_arg_0"], &gelf["_arg_1"]),
+        (&json!("config"), &json!("3"))
+    );
+
+    let levels = [
+        (LogLevel::Error, 3),
+        (LogLevel::Warn, 4),
+        (LogLevel::Info, 6),
+        (LogLevel::Debug, 7),
+        (LogLevel::Trace, 7),
+    ];
+    for (level, syslog) in levels {
+        let evt = LogEvent::new(level, "a.Main", "", &[]);
+        assert_eq!(to_gelf(&evt, "ws001")["level"], syslog);
+    }
+}
@@ -1,48 +1,182 @@
 use jaded::FromJava;
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::{Display, Error as FmtError, Formatter},
     str::FromStr,
+    sync::LazyLock,
 };
-use time::{OffsetDateTime, PrimitiveDateTime};
+use time::{OffsetDateTime, UtcOffset};
 
+mod adapters;
+#[cfg(feature = "serde")]
+mod bunyan;
+mod catalog;
+mod clock;
+mod connect;
+mod correlation;
+#[cfg(feature = "csv")]
+mod delimited;
+mod digest;
+mod fan_out;
+mod filter;
+#[cfg(feature = "serde")]
+mod frames;
+#[cfg(feature = "serde")]
+mod gelf;
+mod pattern;
+mod pipeline;
+#[cfg(feature = "serde")]
+mod record;
+mod stream;
+mod syslog;
+mod table;
+mod tail;
+mod trace;
+
+pub use adapters::{
+    EventStreamExt, GroupByMdc, Meter, Metered, Reorder, SequenceGap, SequenceGaps, StampReceived,
+    Stamped, UntilMarker,
+};
+#[cfg(feature = "serde")]
+pub use bunyan::to_bunyan;
+pub use catalog::{common_logger_prefix, LoggerCatalog, LoggerNode};
+pub use clock::{Clock, SystemClock, TestClock};
+#[cfg(unix)]
+pub use connect::connect_unix;
+pub use connect::{connect_tcp, RetryConfig};
+pub use correlation::{Correlation, CorrelationKeys};
+#[cfg(feature = "csv")]
+pub use delimited::{write_csv_header, write_csv_row, CsvColumn, UnknownColumn};
+pub use digest::{Digest, DigestEntry};
+pub use fan_out::{fan_out, Backpressure, FanOutReceiver};
+pub use filter::Filter;
+#[cfg(feature = "serde")]
+pub use frames::{frame_records, write_frames};
+#[cfg(feature = "serde")]
+pub use gelf::to_gelf;
+pub use pattern::{Pattern, PatternError, PatternErrorKind, WordRegistry};
+pub use pipeline::{Pipeline, Through, Transform};
+#[cfg(feature = "serde")]
+pub use record::{
+    record_from_msgpack, to_json_line, to_json_line_with, to_msgpack, JsonLineOptions, Record,
+};
+pub use stream::{Checkpoint, EventSource, Events, LogEventStream, StreamItem, StreamItems};
+pub use syslog::{to_syslog_rfc5424, to_syslog_rfc5424_with};
+pub use table::TableRenderer;
+pub use tail::{run_tail, TailSummary, UnknownLevels};
+pub use trace::ThrowableRenderer;
+
+#[derive(Debug)]
 pub enum Error {
     UnknownLogLevel(String),
+    Java(jaded::JavaError),
+    Io(std::io::Error),
 }
 
 impl Display for Error {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Self::UnknownLogLevel(msg) => write!(fmt, "Unrecognised log level: {msg:?}"),
+            Self::Java(err) => write!(fmt, "Could not read log event: {err}"),
+            Self::Io(err) => write!(fmt, "IO error: {err}"),
         }
     }
 }
 
-#[derive(Debug, FromJava)]
-#[jaded(rename)]
+impl std::error::Error for Error {}
+
+impl From<jaded::JavaError> for Error {
+    fn from(err: jaded::JavaError) -> Self {
+        Self::Java(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
 pub struct LogEvent {
-    #[jaded(field = "message")]
     template: String,
     thread_name: String,
     pub logger_name: Source,
-    #[jaded(field = "loggerContextVO")]
-    pub context: LogContext,
-    #[jaded(extract(converters::read_i32))]
+    pub context: Option<LogContext>,
     pub level: LogLevel,
-    #[jaded(extract(converters::read_list))]
+    // LoggingEventVO writes each argument as the result of its toString so the
+    // original types are not available.
     arguments: Vec<String>,
-    #[jaded(field = "throwableProxy")]
     pub throwable: Option<Throwable>,
-    #[jaded(field = "callerDataArray")]
     stacktrace: Option<Vec<StackFrame>>,
     pub marker: Option<Marker>,
     time_stamp: i64,
-    #[jaded(field = "mdcPropertyMap", from = "converters::Map")]
     pub mdc: HashMap<String, String>,
+    key_value_pairs: Vec<(String, String)>,
+    sequence_number: Option<i64>,
+}
+
+// Implemented by hand rather than derived as fields added in later versions of
+// logback are missing from events serialized by earlier ones.
+impl FromJava for LogEvent {
+    fn from_value(value: &jaded::Value) -> jaded::ConversionResult<Self> {
+        let data = match value {
+            jaded::Value::Object(data) => data,
+            jaded::Value::Null => return Err(jaded::ConversionError::NullPointerException),
+            _ => return Err(jaded::ConversionError::InvalidType("LoggingEventVO")),
+        };
+        let mut anno = data
+            .get_annotation(0)
+            .ok_or(jaded::ConversionError::MissingAnnotations(0))?;
+        Ok(Self {
+            // Some internal events have no message
+            template: data
+                .get_field_as::<Option<String>>("message")?
+                .unwrap_or_default(),
+            thread_name: data.get_field_as("threadName")?,
+            logger_name: data.get_field_as("loggerName")?,
+            context: data.get_field_as("loggerContextVO")?,
+            level: converters::read_level(data, &mut anno)?.into(),
+            arguments: converters::read_list(&mut anno)?,
+            throwable: data.get_field_as("throwableProxy")?,
+            stacktrace: data.get_field_as("callerDataArray")?,
+            marker: data.get_field_as("marker")?,
+            time_stamp: data.get_field_as("timeStamp")?,
+            // Older versions of logback named the field `mdc`
+            mdc: match converters::optional_field::<converters::Map>(data, "mdcPropertyMap")? {
+                Some(mdc) => mdc.into(),
+                None => converters::optional_field::<converters::Map>(data, "mdc")?
+                    .map_or_else(HashMap::new, Into::into),
+            },
+            key_value_pairs: converters::optional_field::<KeyValuePairs>(data, "keyValuePairList")?
+                .map_or_else(Vec::new, Into::into),
+            // Zero if logback wasn't configured with a sequence number generator
+            sequence_number: converters::optional_field::<i64>(data, "sequenceNumber")?
+                .filter(|&n| n != 0),
+        })
+    }
 }
 
+/// The key value pairs added with SLF4J's fluent API
 #[derive(Debug, FromJava)]
+struct KeyValuePairs(#[jaded(extract(converters::read_list))] Vec<KeyValuePair>);
+
+impl From<KeyValuePairs> for Vec<(String, String)> {
+    fn from(pairs: KeyValuePairs) -> Self {
+        pairs.0.into_iter().map(|p| (p.key, p.value)).collect()
+    }
+}
+
+#[derive(Debug, FromJava)]
+struct KeyValuePair {
+    key: String,
+    #[jaded(from = "converters::Text")]
+    value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, FromJava)]
 #[jaded(from = "String")]
 pub struct Source(String);
 
@@ -62,7 +196,24 @@ impl std::fmt::Display for Source {
 }
 
 impl Source {
-    fn reduced(&self, target: usize) -> Cow<str> {
+    /// This logger with each segment replaced by a token derived from its hash
+    /// so that logs can be shared without revealing package and class names
+    ///
+    /// The result has the same number of segments and is the same in every run
+    /// so events from one logger can still be grouped, eg
+    /// `com.acme.Service` becomes something like `pkg_1f3a.pkg_94c2.Cls_07bd`.
+    pub fn anonymized(&self) -> Source {
+        let mut segments = self.0.split('.').collect::<Vec<_>>();
+        let class = segments.pop().unwrap_or_default();
+        let token = |prefix, seg: &str| format!("{prefix}_{:04x}", fnv1a(seg.as_bytes()) & 0xffff);
+        let mut tokens = segments
+            .into_iter()
+            .map(|seg| token("pkg", seg))
+            .collect::<Vec<_>>();
+        tokens.push(token("Cls", class));
+        Source(tokens.join("."))
+    }
+    fn reduced(&self, target: usize) -> Cow<'_, str> {
         if self.0.len() <= target {
             Cow::Borrowed(&self.0)
         } else {
@@ -85,6 +236,101 @@ impl Source {
     }
 }
 
+#[cfg(feature = "color")]
+impl Source {
+    /// The colours used by [`Source::color`]
+    pub const DEFAULT_PALETTE: [yansi::Color; 6] = [
+        yansi::Color::Red,
+        yansi::Color::Green,
+        yansi::Color::Yellow,
+        yansi::Color::Blue,
+        yansi::Color::Magenta,
+        yansi::Color::Cyan,
+    ];
+    /// A colour for this logger that is the same every time it is seen so that
+    /// events from different loggers are easy to tell apart
+    pub fn color(&self) -> yansi::Color {
+        self.color_from(&Self::DEFAULT_PALETTE)
+    }
+    /// A colour for this logger chosen from the given palette
+    ///
+    /// # Panics
+    /// If the palette is empty
+    pub fn color_from(&self, palette: &[yansi::Color]) -> yansi::Color {
+        let index = fnv1a(self.0.as_bytes()) % palette.len() as u64;
+        palette[index as usize]
+    }
+}
+
+#[cfg(feature = "color")]
+#[test]
+fn test_source_color() {
+    use yansi::Color;
+    let service = Source("com.example.Service".into());
+    assert_eq!(
+        service.color(),
+        Source("com.example.Service".into()).color()
+    );
+    let palette = [Color::White, Color::Black];
+    assert_eq!(
+        service.color_from(&palette),
+        Source("com.example.Service".into()).color_from(&palette)
+    );
+    assert_eq!(service.color_from(&[Color::Blue]), Color::Blue);
+}
+
+#[test]
+fn test_source_map_key() {
+    let events = ["a.Main", "a.db.Pool", "a.Main"]
+        .map(|logger| LogEvent::new(LogLevel::Info, logger, "", &[]));
+    let mut counts = HashMap::<Source, usize>::new();
+    for evt in &events {
+        *counts.entry(evt.logger_name.clone()).or_default() += 1;
+    }
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[&Source("a.Main".into())], 2);
+    assert_eq!(counts[&events[1].logger_name], 1);
+}
+
+#[test]
+fn test_source_anonymized() {
+    let source = Source("com.acme.orders.OrderService".into());
+    let anon = source.anonymized();
+    assert_eq!(anon.0.split('.').count(), 4);
+    assert!(!anon.0.contains("acme") && !anon.0.contains("Order"));
+    assert_eq!(
+        anon.0,
+        Source("com.acme.orders.OrderService".into()).anonymized().0
+    );
+    // Shared packages map to the same token
+    let sibling = Source("com.acme.billing.Invoice".into()).anonymized();
+    assert_eq!(anon.0[..17], sibling.0[..17]);
+    assert_ne!(anon.0, sibling.0);
+    assert!(anon.0.starts_with("pkg_") && anon.0.rsplit('.').next().unwrap().starts_with("Cls_"));
+}
+
+#[test]
+fn test_event_anonymized() {
+    let mut evt = LogEvent::new(
+        LogLevel::Warn,
+        "com.acme.Billing",
+        "Charged {} to {}",
+        &["£12", "alice"],
+    );
+    evt.mdc.insert("user".into(), "alice".into());
+    evt.throwable = Some(Throwable::new(
+        "com.acme.BillingException",
+        &[("com.acme.Billing", "charge")],
+    ));
+    let anon = evt.anonymized();
+    assert_eq!(anon.level, LogLevel::Warn);
+    assert_eq!(anon.message(), "Charged *** to ***");
+    assert_eq!(anon.logger_name.0, evt.logger_name.anonymized().0);
+    assert_eq!(anon.mdc["user"], "***");
+    assert!(anon.throwable.is_none());
+    assert_eq!(anon.time(), evt.time());
+}
+
 #[test]
 fn test_source_reduction() {
     let s = Source("uk.ac.diamond.daq.persistence.jythonshelf".into());
@@ -100,81 +346,1256 @@ fn test_source_reduction() {
     let s = Source("gda.device.scannable.ScannableMotor".into());
     assert_eq!(s.reduced(30), "g.d.scannable.ScannableMotor");
 
-    let s = Source("gdascripts.scan.process.ScanDataProcessorResult.ScanDataProcessorResult".into());
+    let s =
+        Source("gdascripts.scan.process.ScanDataProcessorResult.ScanDataProcessorResult".into());
     assert_eq!(s.reduced(30), "g.s.p.S.ScanDataProcessorResult");
 }
 
 impl LogEvent {
-    pub fn message(&self) -> Cow<str> {
+    pub fn message(&self) -> Cow<'_, str> {
         Self::format(&self.template, &self.arguments)
     }
+    /// The message with arguments substituted according to the given options
+    pub fn message_with(&self, options: &FormatOptions) -> Cow<'_, str> {
+        Self::format_with(&self.template, &self.arguments, options)
+    }
+    /// The message followed by the full trace of the throwable on the next
+    /// line, or just the message if there is no throwable
+    pub fn message_with_exception(&self) -> String {
+        match self.split_message() {
+            (message, Some(trace)) => format!("{message}\n{trace}"),
+            (message, None) => message.into_owned(),
+        }
+    }
+    /// The message and the trace of the throwable (if there is one) rendered
+    /// separately, eg to show in different panes
+    pub fn split_message(&self) -> (Cow<'_, str>, Option<String>) {
+        (
+            self.message(),
+            self.throwable
+                .as_ref()
+                .map(|t| ThrowableRenderer::new().render(t)),
+        )
+    }
+    /// The template if it can be used as the message without substituting any
+    /// arguments, or `None` if the message has to be built with [`Self::message`]
+    ///
+    /// This never allocates so can be used to skip formatting in the common
+    /// case of an event logged without arguments.
+    pub fn message_borrowed(&self) -> Option<&str> {
+        // Escapes are only removed when there are arguments to substitute
+        let unchanged = self.arguments.is_empty()
+            || (Self::anchor_count(&self.template) == 0 && !self.template.contains('\\'));
+        unchanged.then_some(self.template.as_str())
+    }
+    /// The message with any ANSI escape sequences (eg colours) removed
+    pub fn message_plain(&self) -> Cow<'_, str> {
+        match self.message() {
+            Cow::Borrowed(msg) => strip_ansi(msg),
+            Cow::Owned(msg) => Cow::Owned(strip_ansi(&msg).into_owned()),
+        }
+    }
+    /// Whether the message is a JSON object or array, eg from an application
+    /// that logs structured data as its message
+    #[cfg(feature = "serde")]
+    pub fn message_is_json(&self) -> bool {
+        let message = self.message();
+        let message = message.trim();
+        (message.starts_with('{') || message.starts_with('['))
+            && serde_json::from_str::<serde::de::IgnoredAny>(message).is_ok()
+    }
+    /// The message on a single line, with each run of whitespace (including
+    /// line breaks) replaced by a single space and none at either end, eg for
+    /// output that will be searched with `grep`
+    pub fn message_single_line(&self) -> Cow<'_, str> {
+        let message = self.message();
+        let folded = message.trim() != message
+            || message.contains("  ")
+            || message.contains(|c: char| c.is_whitespace() && c != ' ');
+        if folded {
+            Cow::Owned(message.split_whitespace().collect::<Vec<_>>().join(" "))
+        } else {
+            message
+        }
+    }
+    /// The message limited to at most `max` characters
+    ///
+    /// If the message is longer, it is cut short and ends with `…`, which is
+    /// counted as one of the `max` characters.
+    pub fn message_truncated(&self, max: usize) -> Cow<'_, str> {
+        let message = self.message();
+        match message.char_indices().nth(max) {
+            None => message,
+            Some(_) if max == 0 => Cow::Borrowed(""),
+            Some(_) => {
+                let (cut, _) = message.char_indices().nth(max - 1).unwrap();
+                Cow::Owned(format!("{}…", &message[..cut]))
+            }
+        }
+    }
+    /// The parts of this event needed to show it as a single row, eg in a
+    /// terminal viewer that shouldn't keep every full event
+    ///
+    /// The logger is abbreviated to `logger_width` where possible (see
+    /// `%logger{n}` in [`Pattern`]) and the message is
+    /// [truncated](LogEvent::message_truncated) to `preview_len` characters.
+    pub fn summary(&self, logger_width: usize, preview_len: usize) -> EventSummary {
+        EventSummary {
+            time: self.time(),
+            level: self.level,
+            logger_short: self.logger_name.reduced(logger_width).into_owned(),
+            message_preview: self.message_truncated(preview_len).into_owned(),
+        }
+    }
+    /// The entries in the MDC sorted by key, so that they are always in the
+    /// same order when rendered
+    pub fn mdc_sorted(&self) -> Vec<(&str, &str)> {
+        let mut entries = self
+            .mdc
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect::<Vec<_>>();
+        entries.sort_unstable();
+        entries
+    }
+    /// The MDC as a map that iterates in key order
+    pub fn mdc_sorted_map(&self) -> BTreeMap<&str, &str> {
+        self.mdc
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect()
+    }
+    /// The number of arguments that were not used because the template has
+    /// fewer anchors than the event has arguments
+    pub fn unused_argument_count(&self) -> usize {
+        self.arguments
+            .len()
+            .saturating_sub(Self::anchor_count(&self.template))
+    }
+    pub fn has_unused_arguments(&self) -> bool {
+        self.unused_argument_count() > 0
+    }
+    /// The structured key value pairs added to the event with SLF4J's fluent
+    /// API, eg `log.atInfo().addKeyValue("orderId", id)`
+    ///
+    /// Values are converted to strings. Events from versions of logback before
+    /// 1.3 never have any pairs.
+    pub fn key_value_pairs(&self) -> &[(String, String)] {
+        &self.key_value_pairs
+    }
+    /// The trace and span IDs of this event under the default
+    /// [`CorrelationKeys`], if it has a trace ID
+    pub fn correlation(&self) -> Option<Correlation> {
+        self.correlation_with(&CorrelationKeys::default())
+    }
+    /// The trace and span IDs of this event under the given keys, if it has a
+    /// trace ID. Empty values are ignored.
+    pub fn correlation_with(&self, keys: &CorrelationKeys) -> Option<Correlation> {
+        let find = |keys: &[String]| {
+            keys.iter()
+                .filter_map(|key| self.mdc.get(key))
+                .find(|value| !value.is_empty())
+                .cloned()
+        };
+        Some(Correlation {
+            trace_id: find(&keys.trace)?,
+            span_id: find(&keys.span),
+        })
+    }
+    /// The event as a flat map of fields, eg for use with a templating engine
+    ///
+    /// The fields are `level`, `logger`, `message`, `thread` and `timestamp`
+    /// (RFC 3339 in UTC) with each MDC entry added as `mdc.<key>`.
+    pub fn as_fields(&self) -> BTreeMap<Cow<'static, str>, String> {
+        let mut fields = BTreeMap::new();
+        fields.insert("level".into(), self.level.name().into());
+        fields.insert("logger".into(), self.logger_name.0.clone());
+        fields.insert("message".into(), self.message().into_owned());
+        fields.insert("thread".into(), self.thread_name.clone());
+        let time = self.format_time("yyyy-MM-dd'T'HH:mm:ss.SSS'Z'").unwrap();
+        fields.insert("timestamp".into(), time);
+        for (key, value) in &self.mdc {
+            fields.insert(format!("mdc.{key}").into(), value.clone());
+        }
+        fields
+    }
+    /// A rough measure of how heavy an event is, for finding log statements
+    /// that produce unusually large events
+    ///
+    /// This is the total length of the event's text (template, arguments,
+    /// names, MDC and key value pairs) plus the class, method and file names of
+    /// every frame in its throwable and the throwable's causes. It is not the
+    /// size of the serialized event.
+    pub fn approx_size(&self) -> usize {
+        fn text(s: &Option<String>) -> usize {
+            s.as_ref().map_or(0, String::len)
+        }
+        let frame =
+            |f: &StackFrame| text(&f.declaring_class) + text(&f.method_name) + text(&f.file_name);
+        let strings = self.template.len()
+            + self.thread_name.len()
+            + self.logger_name.0.len()
+            + self.arguments.iter().map(String::len).sum::<usize>();
+        let pairs = (self
+            .mdc
+            .iter()
+            .chain(self.key_value_pairs.iter().map(|(k, v)| (k, v))))
+        .map(|(k, v)| k.len() + v.len())
+        .sum::<usize>();
+        let frames = (self.throwable.iter())
+            .flat_map(Throwable::chain)
+            .map(|t| {
+                t.class_name.len()
+                    + text(&t.message)
+                    + t.stack_trace
+                        .iter()
+                        .map(|ste| frame(&ste.ste))
+                        .sum::<usize>()
+            })
+            .sum::<usize>();
+        strings + pairs + frames
+    }
+    /// The arguments logged with the event, including any that weren't
+    /// substituted into the message
+    pub fn arguments(&self) -> &[String] {
+        &self.arguments
+    }
+    /// The number assigned to the event by logback's sequence number generator
+    ///
+    /// This is only available from logback 1.3 and only if a generator was
+    /// configured. Numbers increase by one for each event so can be used to
+    /// detect events that were lost (see
+    /// [`EventStreamExt::detect_gaps_by_sequence`]).
+    pub fn sequence_number(&self) -> Option<i64> {
+        self.sequence_number
+    }
+    /// A compact rendering of up to `max_keys` MDC entries, sorted by key, eg
+    /// `{requestId=abc, tenant=acme, …(+2 more)}`
+    pub fn mdc_summary(&self, max_keys: usize) -> String {
+        let entries = self.mdc_sorted();
+        let mut parts = entries
+            .iter()
+            .take(max_keys)
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>();
+        if entries.len() > max_keys {
+            parts.push(format!("…(+{} more)", entries.len() - max_keys));
+        }
+        format!("{{{}}}", parts.join(", "))
+    }
+    /// The context the event was logged in or an empty default context if it
+    /// wasn't included in the serialized event
+    pub fn context(&self) -> &LogContext {
+        static DEFAULT: LazyLock<LogContext> = LazyLock::new(LogContext::default);
+        self.context.as_ref().unwrap_or(&DEFAULT)
+    }
+    /// Whether the event's marker, or any marker it references, has the given
+    /// name
+    pub fn has_marker(&self, name: &str) -> bool {
+        self.marker.as_ref().is_some_and(|m| m.contains(name))
+    }
+    /// Whether the event was logged by a logger in one of the given packages
+    /// (or by one of the given loggers)
+    pub fn is_application(&self, prefixes: &[&str]) -> bool {
+        in_packages(&self.logger_name.0, prefixes)
+    }
+    /// Whether the event was logged by a logger in one of the packages listed
+    /// in the context's `APP_PACKAGES` property
+    ///
+    /// The property is a comma separated list of packages set in the logback
+    /// configuration, eg `<property scope="context" name="APP_PACKAGES"
+    /// value="com.acme,org.acme"/>`. If it isn't set, no events are from the
+    /// application.
+    pub fn is_application_in_context(&self) -> bool {
+        self.context()
+            .properties
+            .get(LogContext::APP_PACKAGES)
+            .is_some_and(|packages| {
+                let prefixes = packages.split(',').map(str::trim).collect::<Vec<_>>();
+                self.is_application(&prefixes)
+            })
+    }
+    /// The event's marker and every marker it references, depth first
+    pub fn markers(&self) -> impl Iterator<Item = &Marker> {
+        self.marker.iter().flat_map(Marker::iter)
+    }
+    /// Whether caller data was computed for this event
+    ///
+    /// The serialized event has no separate flag for this. As with
+    /// `LoggingEventVO.hasCallerData`, it is inferred from the caller data
+    /// array, which is null when the appender was not configured to include
+    /// it. An empty array is treated as absent as there is nothing to render.
+    pub fn has_caller_data(&self) -> bool {
+        self.stacktrace
+            .as_ref()
+            .is_some_and(|frames| !frames.is_empty())
+    }
+    /// The location the event was logged from, if caller data was included
+    pub fn caller(&self) -> Option<&StackFrame> {
+        self.stacktrace.as_ref()?.first()
+    }
     pub fn time(&self) -> OffsetDateTime {
         let nanos = 1_000_000 * self.time_stamp as i128;
         OffsetDateTime::from_unix_timestamp_nanos(nanos).unwrap()
     }
+    /// The time of the event (in UTC) formatted using the same
+    /// `SimpleDateFormat` style patterns as `%d{...}` in a [`Pattern`], eg
+    /// `HH:mm:ss.SSS`. Returns `None` if the format is not supported.
+    pub fn format_time(&self, format: &str) -> Option<String> {
+        Some(pattern::DateFormat::parse(format)?.format(&self.time()))
+    }
+    /// The time of the event in the time zone given by an MDC entry
+    ///
+    /// The entry should be a UTC offset such as `+01:00`, `-0530` or
+    /// `UTC+2`. If it's missing or isn't a valid offset (including zone names
+    /// like `Europe/London`), the time is in UTC.
+    pub fn time_in_mdc_zone(&self, key: &str) -> OffsetDateTime {
+        let offset = self
+            .mdc
+            .get(key)
+            .and_then(|zone| parse_offset(zone))
+            .unwrap_or(UtcOffset::UTC);
+        self.time().to_offset(offset)
+    }
+    /// The milliseconds between the logging context being created and this
+    /// event, if the context was included
+    pub fn relative_millis(&self) -> Option<i64> {
+        Some(self.time_stamp - self.context.as_ref()?.birth_time)
+    }
+    /// The hour, minute, second and millisecond (in UTC) the event was logged
+    pub fn time_parts(&self) -> (u8, u8, u8, u16) {
+        const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+        let millis = self.time_stamp.rem_euclid(MILLIS_PER_DAY);
+        let seconds = millis / 1000;
+        (
+            (seconds / 3600) as u8,
+            (seconds / 60 % 60) as u8,
+            (seconds % 60) as u8,
+            (millis % 1000) as u16,
+        )
+    }
+    /// The [first line](Throwable::first_line) of the event's throwable, if it
+    /// has one
+    pub fn exception_headline(&self) -> Option<String> {
+        self.throwable.as_ref().map(Throwable::first_line)
+    }
+    /// The event's throwable rendered by the default [`ThrowableRenderer`]
+    /// on a new line, or an empty string if there isn't one
     pub fn stack(&self) -> String {
         match &self.throwable {
-            Some(t) => format!("\n{}{}", t.class_name, t.trace()),
-            None => format!(""),
+            Some(t) => format!("\n{}", ThrowableRenderer::new().render(t)),
+            None => String::new(),
         }
     }
+    /// A key identifying events logged by the same statement for the same
+    /// reason, eg `ERROR|com.acme.Foo|NullPointerException|user {} not found`
+    ///
+    /// The key is made up of the level, logger, the simple class name of the
+    /// throwable (empty if there isn't one) and the unformatted message so
+    /// events differing only in their arguments or time share a key.
+    pub fn alert_key(&self) -> String {
+        let throwable = self
+            .throwable
+            .as_ref()
+            .map_or("", |t| t.class_name.rsplit('.').next().unwrap());
+        format!(
+            "{}|{}|{}|{}",
+            self.level, self.logger_name.0, throwable, self.template
+        )
+    }
+    /// A hash of the [`alert_key`](Self::alert_key) for grouping events
+    /// logged by the same statement for the same reason
+    ///
+    /// The hash (64 bit FNV-1a) is stable so fingerprints can be stored and
+    /// compared between runs.
+    pub fn fingerprint(&self) -> u64 {
+        fnv1a(self.alert_key().as_bytes())
+    }
+    /// Whether this and another event have the same fingerprint and were
+    /// logged within `window` of each other, in either order. Used to collapse
+    /// bursts of repeated events even when other events are logged between
+    /// them.
+    pub fn same_burst_as(&self, other: &LogEvent, window: std::time::Duration) -> bool {
+        let apart = self.time_stamp.abs_diff(other.time_stamp);
+        u128::from(apart) <= window.as_millis() && self.fingerprint() == other.fingerprint()
+    }
+    /// A copy of this event that can be shared outside the organisation that
+    /// logged it
+    ///
+    /// The level, timing, thread, markers and message template are kept but the
+    /// logger is [anonymized](Source::anonymized), every argument and MDC or
+    /// key value pair value is replaced with `***` and the throwable, caller
+    /// data and logger context, which name application classes, are dropped.
+    pub fn anonymized(&self) -> LogEvent {
+        const SCRUBBED: &str = "***";
+        LogEvent {
+            template: self.template.clone(),
+            thread_name: self.thread_name.clone(),
+            logger_name: self.logger_name.anonymized(),
+            context: None,
+            level: self.level,
+            arguments: vec![SCRUBBED.into(); self.arguments.len()],
+            throwable: None,
+            stacktrace: None,
+            marker: self.marker.clone(),
+            time_stamp: self.time_stamp,
+            mdc: self
+                .mdc
+                .keys()
+                .map(|key| (key.clone(), SCRUBBED.into()))
+                .collect(),
+            key_value_pairs: self
+                .key_value_pairs
+                .iter()
+                .map(|(key, _)| (key.clone(), SCRUBBED.into()))
+                .collect(),
+            sequence_number: self.sequence_number,
+        }
+    }
+    /// The number of `{}` anchors in a template, ignoring escaped ones
+    fn anchor_count(template: &str) -> usize {
+        let mut count = 0;
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let _ = chars.next();
+                }
+                '{' if chars.next_if_eq(&'}').is_some() => count += 1,
+                _ => {}
+            }
+        }
+        count
+    }
     fn format<'a>(template: &'a str, args: &[String]) -> Cow<'a, str> {
-        const ANCHOR: &str = "{}";
+        Self::format_with(template, args, &FormatOptions::default())
+    }
+    fn format_with<'a>(
+        template: &'a str,
+        args: &[String],
+        options: &FormatOptions,
+    ) -> Cow<'a, str> {
         const ESC: char = '\\';
         const OPEN: char = '{';
         const CLOSE: char = '}';
         const NULL_STRING: &str = "NULL_ARGUMENT_ARRAY_ELEMENT";
         const NULL: &str = "null";
-        if !args.is_empty() && template.contains("{}") {
-            let mut message = String::new();
-            let mut args = args.iter();
-            let mut chars = template.chars().peekable();
-            while let Some(c) = chars.next() {
-                match c {
-                    ESC => match chars.next() {
-                        Some(OPEN) if chars.peek() == Some(&CLOSE) => message.push(OPEN),
-                        Some(c) => {
-                            // If the escape isn't escaping a complete {},
-                            // include the escape in the message
-                            message.push(ESC);
-                            message.push(c);
+        // Until the first change, the message is a copy of the template so it is
+        // only built once something is substituted.
+        fn edit<'m>(message: &'m mut Option<String>, prefix: &str) -> &'m mut String {
+            message.get_or_insert_with(|| prefix.to_owned())
+        }
+        if args.is_empty() && options.on_missing_arg == MissingArg::KeepAnchor {
+            return Cow::Borrowed(template);
+        }
+        let mut message = None;
+        let mut args = args.iter();
+        let mut chars = template.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                ESC => match chars.next() {
+                    Some((_, OPEN)) if matches!(chars.peek(), Some((_, CLOSE))) => {
+                        edit(&mut message, &template[..i]).push(OPEN)
+                    }
+                    Some((_, c)) => {
+                        // If the escape isn't escaping a complete {},
+                        // include the escape in the message
+                        if let Some(m) = &mut message {
+                            m.push(ESC);
+                            m.push(c);
+                        }
+                    }
+                    None => {
+                        if let Some(m) = &mut message {
+                            m.push(ESC)
                         }
-                        None => message.push(ESC),
-                    },
-                    OPEN => match chars.peek() {
-                        Some(&CLOSE) => {
-                            let _ = chars.next(); // drop closing char
-                            match args.next().map(String::as_str) {
-                                Some(NULL_STRING) => message.push_str(NULL),
-                                Some(a) => message.push_str(a),
-                                None => {
-                                    message.push_str(ANCHOR);
-                                    chars.for_each(|c| message.push(c));
-                                    break;
+                    }
+                },
+                OPEN if matches!(chars.peek(), Some((_, CLOSE))) => {
+                    let _ = chars.next(); // drop closing char
+                    match args.next().map(String::as_str) {
+                        Some(NULL_STRING) => edit(&mut message, &template[..i]).push_str(NULL),
+                        Some(a) => edit(&mut message, &template[..i]).push_str(a),
+                        None => match &options.on_missing_arg {
+                            MissingArg::KeepAnchor => {
+                                // The remaining anchors are left unfilled
+                                if let Some(m) = &mut message {
+                                    m.push_str(&template[i..]);
                                 }
+                                break;
                             }
-                        }
-                        _ => message.push(OPEN),
-                    },
-                    c => message.push(c),
+                            MissingArg::Placeholder(text) => {
+                                edit(&mut message, &template[..i]).push_str(text)
+                            }
+                            MissingArg::Empty => {
+                                edit(&mut message, &template[..i]);
+                            }
+                        },
+                    }
+                }
+                c => {
+                    if let Some(m) = &mut message {
+                        m.push(c)
+                    }
                 }
             }
-            Cow::Owned(message)
-        } else {
-            Cow::Borrowed(template)
         }
+        message.map_or(Cow::Borrowed(template), Cow::Owned)
+    }
+}
+
+/// Whether a class or logger name is one of, or is in one of, the given
+/// packages
+fn in_packages(name: &str, prefixes: &[&str]) -> bool {
+    prefixes.iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+/// 64 bit FNV-1a hash, used where hashes need to be the same between runs and
+/// releases
+fn fnv1a(data: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    data.iter()
+        .fold(OFFSET, |hash, &b| (hash ^ b as u64).wrapping_mul(PRIME))
+}
+
+/// Remove ANSI escape sequences from text
+///
+/// Control sequences (`ESC [ ... final`) and operating system commands
+/// (`ESC ] ... BEL` or `ESC ] ... ESC \`) are removed entirely, as is any
+/// other escape followed by a single character.
+pub(crate) fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains('\x1b') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                // Parameter and intermediate bytes up to a final byte
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next().is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Parse a UTC offset written as `Z`, `UTC`, `[+-]HH`, `[+-]HH:MM` or
+/// `[+-]HHMM`, optionally prefixed by `UTC` or `GMT`
+fn parse_offset(zone: &str) -> Option<UtcOffset> {
+    let zone = zone.trim();
+    let offset = zone
+        .strip_prefix("UTC")
+        .or_else(|| zone.strip_prefix("GMT"))
+        .unwrap_or(zone);
+    if offset.is_empty() || offset == "Z" {
+        return Some(UtcOffset::UTC);
+    }
+    let (sign, digits) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+        (Some(rest), _) => (1, rest),
+        (_, Some(rest)) => (-1, rest),
+        _ => return None,
+    };
+    // Only ASCII so the digits can be split by byte position
+    if !digits.bytes().all(|b| b.is_ascii_digit() || b == b':') {
+        return None;
+    }
+    let (hours, minutes) = match digits.split_once(':') {
+        Some(parts) => parts,
+        None if digits.len() > 2 => digits.split_at(2),
+        None => (digits, "0"),
+    };
+    let hours = hours.parse::<i8>().ok()?;
+    let minutes = minutes.parse::<i8>().ok()?;
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0).ok()
+}
+
+/// A compact copy of the parts of an event shown in a list of events, see
+/// [`LogEvent::summary`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSummary {
+    pub time: OffsetDateTime,
+    pub level: LogLevel,
+    pub logger_short: String,
+    pub message_preview: String,
+}
+
+/// Options for how the arguments of an event are substituted into its
+/// template
+///
+/// The default options format messages the same way as [`LogEvent::message`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub on_missing_arg: MissingArg,
+}
+
+/// The line ending used when rendering multi-line output, eg stack traces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+    /// Convert text using `\n` for new lines to use this line ending instead.
+    /// Lines already ending in `\r\n` are left as they are.
+    pub fn convert<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        if *self == LineEnding::Lf || !text.contains('\n') {
+            return Cow::Borrowed(text);
+        }
+        let mut out = String::with_capacity(text.len() + text.len() / 16);
+        let mut last = None;
+        for c in text.chars() {
+            if c == '\n' && last != Some('\r') {
+                out.push('\r');
+            }
+            out.push(c);
+            last = Some(c);
+        }
+        Cow::Owned(out)
+    }
+    /// [`Self::convert`] without copying text that doesn't need converting
+    pub(crate) fn convert_owned(&self, text: String) -> String {
+        match self.convert(&text) {
+            Cow::Borrowed(_) => text,
+            Cow::Owned(converted) => converted,
+        }
+    }
+}
+
+#[test]
+fn test_line_ending() {
+    assert_eq!(LineEnding::Lf.convert("a\nb"), "a\nb");
+    assert_eq!(LineEnding::CrLf.convert("a\nb\r\nc\n"), "a\r\nb\r\nc\r\n");
+    assert!(matches!(LineEnding::CrLf.convert("a"), Cow::Borrowed("a")));
+}
+
+/// What to do with anchors in a template after all arguments have been used
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MissingArg {
+    /// Leave the anchor and the rest of the template unchanged, matching
+    /// logback's behaviour
+    #[default]
+    KeepAnchor,
+    /// Replace each unfilled anchor with the given text
+    Placeholder(String),
+    /// Remove unfilled anchors
+    Empty,
+}
+
+/// Convert a value that has already been read from a stream, eg after
+/// checking its class
+impl TryFrom<&jaded::Value> for LogEvent {
+    type Error = Error;
+    fn try_from(value: &jaded::Value) -> Result<Self, Self::Error> {
+        Self::from_value(value).map_err(|e| jaded::JavaError::from(e).into())
+    }
+}
+
+#[cfg(test)]
+impl LogEvent {
+    fn new(level: LogLevel, logger: &str, template: &str, arguments: &[&str]) -> Self {
+        Self {
+            template: template.into(),
+            thread_name: "main".into(),
+            logger_name: Source(logger.into()),
+            context: Some(LogContext::default()),
+            level,
+            arguments: arguments.iter().map(|&a| a.into()).collect(),
+            throwable: None,
+            stacktrace: None,
+            marker: None,
+            time_stamp: 0,
+            mdc: HashMap::new(),
+            key_value_pairs: vec![],
+            sequence_number: None,
+        }
+    }
+}
+
+#[cfg(test)]
+fn read_event(data: &[u8]) -> LogEvent {
+    LogEventStream::new(data).unwrap().next().unwrap().unwrap()
+}
+
+#[test]
+fn test_try_from_value() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let mut parser = jaded::Parser::new(data).unwrap();
+    let value = match parser.read().unwrap() {
+        jaded::Content::Object(value) => value,
+        jaded::Content::Block(_) => panic!("expected an object"),
+    };
+    let evt = LogEvent::try_from(&value).unwrap();
+    assert_eq!(evt.message(), "event 1");
+
+    let value = jaded::Value::Primitive(jaded::PrimitiveType::Int(42));
+    assert!(matches!(LogEvent::try_from(&value), Err(Error::Java(_))));
+}
+
+#[test]
+fn test_null_message() {
+    let evt = read_event(include_bytes!("../tests/fixtures/null_message.ser"));
+    assert_eq!(evt.message(), "");
+    assert_eq!(evt.logger_name.0, "com.example.Service");
+}
+
+#[test]
+fn test_shared_empty_mdc() {
+    // The second event's MDC is a back-reference to the first's
+    let data: &[u8] = include_bytes!("../tests/fixtures/shared_empty_mdc.ser");
+    let events = LogEventStream::new(data)
+        .unwrap()
+        .map(|evt| evt.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|evt| evt.mdc.is_empty()));
+    assert_eq!(events[1].message(), "second");
+}
+
+#[test]
+fn test_synchronized_context_properties() {
+    let evt = read_event(include_bytes!("../tests/fixtures/sync_context.ser"));
+    let context = evt.context.as_ref().unwrap();
+    assert_eq!(context.name(), "default");
+    assert_eq!(context.properties["HOSTNAME"], "ws001");
+}
+
+#[test]
+fn test_key_value_pairs() {
+    let evt = read_event(include_bytes!("../tests/fixtures/key_value_pairs.ser"));
+    assert_eq!(evt.message(), "Order placed");
+    assert_eq!(
+        evt.key_value_pairs(),
+        [
+            ("orderId".to_string(), "A-17".to_string()),
+            ("items".to_string(), "3".to_string())
+        ]
+    );
+
+    let evt = read_event(include_bytes!("../tests/fixtures/five_events.ser"));
+    assert!(evt.key_value_pairs().is_empty());
+}
+
+#[test]
+fn test_sequence_number() {
+    let evt = read_event(include_bytes!("../tests/fixtures/key_value_pairs.ser"));
+    assert_eq!(evt.sequence_number(), Some(1));
+    let evt = read_event(include_bytes!("../tests/fixtures/five_events.ser"));
+    assert_eq!(evt.sequence_number(), None);
+}
+
+#[test]
+fn test_caller() {
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "starting", &[]);
+    assert!(evt.caller().is_none());
+    evt.stacktrace = Some(vec![
+        StackFrame::new("a.Main", "start", 12),
+        StackFrame::new("a.Main", "main", 4),
+    ]);
+    let caller = evt.caller().unwrap();
+    assert_eq!(caller.class_name(), Some("a.Main"));
+    assert_eq!(caller.method_name(), Some("start"));
+    assert_eq!(caller.file_name(), Some("Main.java"));
+    assert_eq!(caller.line(), 12);
+}
+
+#[test]
+fn test_format_options_default() {
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "{} and {}", &["one"]);
+    assert_eq!(
+        FormatOptions::default().on_missing_arg,
+        MissingArg::KeepAnchor
+    );
+    assert_eq!(evt.message_with(&FormatOptions::default()), evt.message());
+    let options = FormatOptions {
+        on_missing_arg: MissingArg::Empty,
+    };
+    assert_eq!(evt.message_with(&options), "one and ");
+}
+
+#[test]
+fn test_line_ending_default() {
+    assert_eq!(LineEnding::default(), LineEnding::Lf);
+}
+
+#[test]
+fn test_message_plain() {
+    let evt = LogEvent::new(
+        LogLevel::Info,
+        "a.Main",
+        "\x1b[1;31mfailed\x1b[0m to reach {}",
+        &["\x1b]8;;http://host\x1b\\host\x1b]8;;\x07"],
+    );
+    assert_eq!(evt.message_plain(), "failed to reach host");
+
+    let plain = LogEvent::new(LogLevel::Info, "a.Main", "no colour", &[]);
+    assert!(matches!(plain.message_plain(), Cow::Borrowed("no colour")));
+}
+
+#[test]
+fn test_has_caller_data() {
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "starting", &[]);
+    assert!(!evt.has_caller_data());
+    evt.stacktrace = Some(vec![]);
+    assert!(!evt.has_caller_data());
+    evt.stacktrace = Some(vec![StackFrame::new("a.Main", "start", 12)]);
+    assert!(evt.has_caller_data());
+}
+
+#[test]
+fn test_message_with_exception() {
+    let mut evt = LogEvent::new(LogLevel::Error, "a.Main", "Failed to {}", &["start"]);
+    assert_eq!(evt.message_with_exception(), "Failed to start");
+    evt.throwable = Some(
+        Throwable::new("a.StartupException", &[("a.Main", "main")]).caused_by(Throwable::new(
+            "java.io.IOException",
+            &[("a.Reader", "read"), ("a.Main", "main")],
+        )),
+    );
+    assert_eq!(
+        evt.message_with_exception(),
+        "Failed to start\n\
+         a.StartupException\n     \
+         at a.Main.main(Main.java:1)\n\
+         Caused by: java.io.IOException\n     \
+         at a.Reader.read(Reader.java:1)\n     \
+         at a.Main.main(Main.java:2)"
+    );
+}
+
+#[test]
+fn test_split_message() {
+    let mut evt = LogEvent::new(LogLevel::Error, "a.Main", "Failed to {}", &["start"]);
+    assert_eq!(
+        evt.split_message(),
+        (Cow::Borrowed("Failed to start"), None)
+    );
+    evt.throwable = Some(
+        Throwable::new("java.io.IOException", &[("a.Main", "main")])
+            .caused_by(Throwable::new("a.DiskFull", &[])),
+    );
+    let (message, trace) = evt.split_message();
+    assert_eq!(message, "Failed to start");
+    assert_eq!(
+        trace.as_deref(),
+        Some("java.io.IOException\n     at a.Main.main(Main.java:1)\nCaused by: a.DiskFull")
+    );
+}
+
+#[test]
+fn test_message_borrowed() {
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "Started {}", &[]);
+    assert_eq!(evt.message_borrowed(), Some("Started {}"));
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "Started", &["unused"]);
+    assert_eq!(evt.message_borrowed(), Some("Started"));
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "Started {}", &["app"]);
+    assert_eq!(evt.message_borrowed(), None);
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", r"Escaped \{}", &["app"]);
+    assert_eq!(evt.message_borrowed(), None);
+    assert_eq!(evt.message(), "Escaped {}");
+}
+
+#[test]
+fn test_message_truncated() {
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "Hello {}", &["world"]);
+    assert_eq!(evt.message_truncated(20), "Hello world");
+    assert_eq!(evt.message_truncated(11), "Hello world");
+    assert_eq!(evt.message_truncated(10), "Hello wor…");
+    assert_eq!(evt.message_truncated(1), "…");
+    assert_eq!(evt.message_truncated(0), "");
+
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "Grüße, {}", &["世界"]);
+    assert_eq!(evt.message_truncated(9), "Grüße, 世界");
+    assert_eq!(evt.message_truncated(8), "Grüße, …");
+    assert_eq!(evt.message_truncated(4), "Grü…");
+    assert_eq!(evt.message_truncated(3), "Gr…");
+}
+
+#[test]
+fn test_mdc_sorted() {
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    evt.mdc.insert("user".into(), "abc".into());
+    evt.mdc.insert("request".into(), "42".into());
+    evt.mdc.insert("app".into(), "demo".into());
+    let expected = [("app", "demo"), ("request", "42"), ("user", "abc")];
+    assert_eq!(evt.mdc_sorted(), expected);
+    assert_eq!(evt.mdc_sorted(), evt.mdc_sorted());
+    let keys = evt.mdc_sorted_map().into_keys().collect::<Vec<_>>();
+    assert_eq!(keys, ["app", "request", "user"]);
+    assert_eq!(evt.mdc_sorted_map().get("request"), Some(&"42"));
+}
+
+#[test]
+fn test_unused_arguments() {
+    let exact = LogEvent::new(LogLevel::Info, "a.Main", "{} of {}", &["3", "4"]);
+    assert_eq!(exact.unused_argument_count(), 0);
+    assert!(!exact.has_unused_arguments());
+
+    let many = LogEvent::new(LogLevel::Info, "a.Main", r"{} of \\{}", &["3", "4", "5"]);
+    assert_eq!(many.unused_argument_count(), 1);
+    assert!(many.has_unused_arguments());
+
+    let escaped = LogEvent::new(LogLevel::Info, "a.Main", r"{} of \{}", &["3", "4"]);
+    assert_eq!(escaped.unused_argument_count(), 1);
+
+    let few = LogEvent::new(LogLevel::Info, "a.Main", "{} of {}", &["3"]);
+    assert_eq!(few.unused_argument_count(), 0);
+    assert!(!few.has_unused_arguments());
+}
+
+#[test]
+fn test_format_time() {
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    evt.time_stamp = 1_689_000_001_500;
+    assert_eq!(
+        evt.format_time("dd/MM/yyyy 'at' HH:mm:ss.SSS").as_deref(),
+        Some("10/07/2023 at 14:40:01.500")
+    );
+    assert_eq!(evt.format_time("HH:mm").as_deref(), Some("14:40"));
+    assert_eq!(evt.format_time("EEE HH:mm"), None);
+}
+
+#[test]
+fn test_time_in_mdc_zone() {
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    evt.time_stamp = 1_689_000_001_500;
+    let utc = evt.time_in_mdc_zone("tz");
+    assert_eq!((utc.hour(), utc.offset()), (14, UtcOffset::UTC));
+
+    for (zone, hour, minute) in [
+        ("+01:00", 15, 40),
+        ("-0530", 9, 10),
+        ("UTC+2", 16, 40),
+        ("Z", 14, 40),
+        ("Europe/London", 14, 40),
+        ("+01:75", 14, 40),
+        ("€1", 14, 40),
+        ("東京", 14, 40),
+        ("+1€", 14, 40),
+        ("-é:30", 14, 40),
+    ] {
+        evt.mdc.insert("tz".into(), zone.into());
+        let time = evt.time_in_mdc_zone("tz");
+        assert_eq!((time.hour(), time.minute()), (hour, minute), "{zone}");
+        assert_eq!(time, evt.time());
+    }
+}
+
+#[test]
+fn test_relative_millis() {
+    let evt = read_event(include_bytes!("../tests/fixtures/exception.ser"));
+    assert_eq!(evt.relative_millis(), Some(1500));
+    let evt = read_event(include_bytes!("../tests/fixtures/null_context.ser"));
+    assert_eq!(evt.relative_millis(), None);
+}
+
+#[test]
+fn test_time_parts() {
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    assert_eq!(evt.time_parts(), (0, 0, 0, 0));
+    evt.time_stamp = 1_689_000_001_500;
+    assert_eq!(evt.time_parts(), (14, 40, 1, 500));
+    let time = evt.time();
+    assert_eq!(
+        evt.time_parts(),
+        (
+            time.hour(),
+            time.minute(),
+            time.second(),
+            time.millisecond()
+        )
+    );
+    evt.time_stamp = -1;
+    assert_eq!(evt.time_parts(), (23, 59, 59, 999));
+}
+
+#[test]
+fn test_fingerprint() {
+    let first = LogEvent::new(LogLevel::Error, "a.Service", "User {} not found", &["a"]);
+    let second = LogEvent::new(LogLevel::Error, "a.Service", "User {} not found", &["b"]);
+    let other = LogEvent::new(LogLevel::Warn, "a.Service", "User {} not found", &["a"]);
+    assert_eq!(first.fingerprint(), second.fingerprint());
+    assert_ne!(first.fingerprint(), other.fingerprint());
+    // FNV-1a of "INFO|||" so fingerprints don't change between releases
+    let empty = LogEvent::new(LogLevel::Info, "", "", &[]);
+    assert_eq!(empty.fingerprint(), 0x5ae2_04e0_bd46_29db);
+}
+
+#[test]
+fn test_as_fields() {
+    let mut evt = LogEvent::new(LogLevel::Warn, "a.Main", "Disk {} full", &["/tmp"]);
+    evt.time_stamp = 1_689_000_001_500;
+    evt.mdc.insert("user".into(), "abc".into());
+    evt.mdc.insert("request.id".into(), "42".into());
+    let fields = evt.as_fields();
+    assert_eq!(
+        fields.keys().collect::<Vec<_>>(),
+        [
+            "level",
+            "logger",
+            "mdc.request.id",
+            "mdc.user",
+            "message",
+            "thread",
+            "timestamp"
+        ]
+    );
+    assert_eq!(fields["level"], "WARN");
+    assert_eq!(fields["message"], "Disk /tmp full");
+    assert_eq!(fields["timestamp"], "2023-07-10T14:40:01.500Z");
+    assert_eq!(fields["mdc.user"], "abc");
+}
+
+#[test]
+fn test_approx_size() {
+    let small = LogEvent::new(LogLevel::Info, "a.Main", "started", &[]);
+    assert_eq!(
+        small.approx_size(),
+        "started".len() + "main".len() + "a.Main".len()
+    );
+
+    let mut large = LogEvent::new(LogLevel::Info, "a.Main", "started {}", &["now"]);
+    large.mdc.insert("user".into(), "abc".into());
+    assert_eq!(large.approx_size(), small.approx_size() + 3 + 3 + 7);
+    let size = large.approx_size();
+    large.throwable = Some(
+        Throwable::new("a.Error", &[("a.Main", "run")])
+            .caused_by(Throwable::new("a.Root", &[("a.Io", "read")])),
+    );
+    // Class, method and file names of each frame as well as the class names
+    let frames = ("a.Main".len() + "run".len() + "Main.java".len())
+        + ("a.Io".len() + "read".len() + "Io.java".len());
+    assert_eq!(
+        large.approx_size(),
+        size + "a.Error".len() + "a.Root".len() + frames
+    );
+}
+
+#[test]
+fn test_same_burst_as() {
+    use std::time::Duration;
+    let mut first = LogEvent::new(LogLevel::Error, "a.Service", "Timeout on {}", &["a"]);
+    let mut second = LogEvent::new(LogLevel::Error, "a.Service", "Timeout on {}", &["b"]);
+    let other = LogEvent::new(LogLevel::Error, "a.Service", "Retrying {}", &["a"]);
+    first.time_stamp = 10_000;
+    second.time_stamp = 10_400;
+    let window = Duration::from_millis(500);
+    assert!(first.same_burst_as(&second, window));
+    assert!(second.same_burst_as(&first, window));
+    assert!(!first.same_burst_as(&other, window));
+    second.time_stamp = 10_501;
+    assert!(!first.same_burst_as(&second, window));
+}
+
+#[test]
+fn test_mdc_summary() {
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    assert_eq!(evt.mdc_summary(3), "{}");
+    evt.mdc.insert("tenant".into(), "acme".into());
+    evt.mdc.insert("requestId".into(), "abc".into());
+    assert_eq!(evt.mdc_summary(3), "{requestId=abc, tenant=acme}");
+    assert_eq!(evt.mdc_summary(2), "{requestId=abc, tenant=acme}");
+    evt.mdc.insert("user".into(), "bob".into());
+    evt.mdc.insert("session".into(), "s1".into());
+    assert_eq!(
+        evt.mdc_summary(2),
+        "{requestId=abc, session=s1, …(+2 more)}"
+    );
+    assert_eq!(evt.mdc_summary(0), "{…(+4 more)}");
+}
+
+#[test]
+fn test_alert_key() {
+    let mut first = LogEvent::new(LogLevel::Error, "com.acme.Foo", "user {} not found", &["a"]);
+    first.throwable = Some(Throwable::new("java.lang.NullPointerException", &[]));
+    let mut second = LogEvent::new(LogLevel::Error, "com.acme.Foo", "user {} not found", &["b"]);
+    second.throwable = Some(Throwable::new("java.lang.NullPointerException", &[]));
+    second.time_stamp = 1_689_000_000_000;
+    assert_eq!(
+        first.alert_key(),
+        "ERROR|com.acme.Foo|NullPointerException|user {} not found"
+    );
+    assert_eq!(first.alert_key(), second.alert_key());
+
+    let plain = LogEvent::new(LogLevel::Warn, "com.acme.Foo", "retrying", &[]);
+    assert_eq!(plain.alert_key(), "WARN|com.acme.Foo||retrying");
+}
+
+#[allow(dead_code)]
+#[derive(Debug, FromJava)]
+#[jaded(rename)]
+pub struct LogContext {
+    birth_time: i64,
+    name: String,
+    #[jaded(field = "propertyMap", from = "converters::Map")]
+    pub properties: HashMap<String, String>,
+}
+
+impl LogContext {
+    /// The context property listing the application's packages
+    pub const APP_PACKAGES: &'static str = "APP_PACKAGES";
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Default for LogContext {
+    fn default() -> Self {
+        Self {
+            birth_time: 0,
+            name: "default".into(),
+            properties: HashMap::new(),
+        }
+    }
+}
+
+#[test]
+fn test_is_application() {
+    let app = LogEvent::new(LogLevel::Info, "com.acme.orders.Service", "", &[]);
+    let framework = LogEvent::new(LogLevel::Info, "org.hibernate.SQL", "", &[]);
+    let similar = LogEvent::new(LogLevel::Info, "com.acmetools.Tool", "", &[]);
+    let prefixes = ["com.acme", "org.acme"];
+    assert!(app.is_application(&prefixes));
+    assert!(!framework.is_application(&prefixes));
+    assert!(!similar.is_application(&prefixes));
+
+    assert!(!app.is_application_in_context());
+    let mut app = app;
+    app.context
+        .as_mut()
+        .unwrap()
+        .properties
+        .insert(LogContext::APP_PACKAGES.into(), "org.acme, com.acme".into());
+    assert!(app.is_application_in_context());
+}
+
+#[test]
+fn test_level_object() {
+    let events = LogEventStream::new(&include_bytes!("../tests/fixtures/level_object.ser")[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let levels = events.iter().map(|evt| evt.level).collect::<Vec<_>>();
+    assert_eq!(levels, [LogLevel::Warn, LogLevel::Error]);
+    assert_eq!(events[0].message(), "Level as object");
+
+    let kept = LogEventStream::new(&include_bytes!("../tests/fixtures/level_object.ser")[..])
+        .unwrap()
+        .with_min_level(LogLevel::Error)
+        .map(|evt| evt.unwrap().level)
+        .collect::<Vec<_>>();
+    assert_eq!(kept, [LogLevel::Error]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_message_is_json() {
+    let json = LogEvent::new(
+        LogLevel::Info,
+        "a.Main",
+        r#" {"order": "{}", "items": [1, 2]}"#,
+        &["A-17"],
+    );
+    assert!(json.message_is_json());
+    assert!(LogEvent::new(LogLevel::Info, "a.Main", "[1, 2]", &[]).message_is_json());
+    assert!(!LogEvent::new(LogLevel::Info, "a.Main", "Loaded {}", &["config"]).message_is_json());
+    assert!(!LogEvent::new(LogLevel::Info, "a.Main", "[main] started", &[]).message_is_json());
+    assert!(!LogEvent::new(LogLevel::Info, "a.Main", "42", &[]).message_is_json());
+}
+
+#[test]
+fn test_message_single_line() {
+    let evt = LogEvent::new(
+        LogLevel::Info,
+        "a.Main",
+        "Query failed:\n  SELECT *\r\n\tFROM {}  \n",
+        &["orders"],
+    );
+    assert_eq!(
+        evt.message_single_line(),
+        "Query failed: SELECT * FROM orders"
+    );
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "Already on one line", &[]);
+    assert!(matches!(
+        evt.message_single_line(),
+        Cow::Borrowed("Already on one line")
+    ));
+}
+
+#[test]
+fn test_summary() {
+    let mut evt = LogEvent::new(
+        LogLevel::Warn,
+        "com.example.orders.OrderService",
+        "Order {} was not found in the ünified store",
+        &["A-17"],
+    );
+    evt.time_stamp = 1_689_000_001_500;
+    let summary = evt.summary(20, 25);
+    assert_eq!(summary.logger_short, "c.e.o.OrderService");
+    assert_eq!(summary.message_preview, "Order A-17 was not found…");
+    assert_eq!(summary.message_preview.chars().count(), 25);
+    assert_eq!(summary.level, LogLevel::Warn);
+    assert_eq!(summary.time, evt.time());
+
+    let summary = evt.summary(50, 40);
+    assert_eq!(summary.logger_short, "com.example.orders.OrderService");
+    assert_eq!(
+        summary.message_preview,
+        "Order A-17 was not found in the ünified…"
+    );
+}
+
+#[test]
+fn test_mdc_field_names() {
+    for data in [
+        &include_bytes!("../tests/fixtures/mdc_property_map.ser")[..],
+        &include_bytes!("../tests/fixtures/mdc_legacy.ser")[..],
+    ] {
+        let evt = read_event(data);
+        assert_eq!(evt.mdc_sorted(), [("requestId", "r-42"), ("user", "abc")]);
     }
 }
 
-#[derive(Debug, FromJava)]
-#[jaded(rename)]
-pub struct LogContext {
-    birth_time: i64,
-    name: String,
-    #[jaded(field = "propertyMap", from = "PropertyMap")]
-    pub properties: HashMap<String, String>,
+#[test]
+fn test_null_context() {
+    let evt = read_event(include_bytes!("../tests/fixtures/null_context.ser"));
+    assert!(evt.context.is_none());
+    assert_eq!(evt.context().name(), "default");
+    assert!(evt.context().properties.is_empty());
+    assert_eq!(evt.message(), "No context");
 }
 
+#[allow(dead_code)]
 #[derive(Debug, FromJava)]
 pub struct Throwable {
     #[jaded(field = "className")]
@@ -189,15 +1610,293 @@ pub struct Throwable {
 }
 
 impl Throwable {
-    fn trace(&self) -> String {
-        self.stack_trace
-            .iter()
-            .map(|ste| format!("{}", ste))
-            .collect::<Vec<_>>()
-            .join("\n     at ")
+    pub fn class_name(&self) -> &str {
+        &self.class_name
+    }
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+    /// The class and message, as the JVM's `Throwable.toString` would render
+    /// them, eg `java.io.IOException: Disk full`. The colon is left out if there
+    /// is no message.
+    pub fn first_line(&self) -> String {
+        match &self.message {
+            Some(msg) => format!("{}: {msg}", self.class_name),
+            None => self.class_name.clone(),
+        }
+    }
+    /// The deepest throwable in the chain of causes of this one, or this
+    /// throwable itself if it has no cause
+    pub fn root_cause(&self) -> &Throwable {
+        self.chain().last().unwrap_or(self)
+    }
+    /// This throwable followed by its cause, the cause's cause and so on
+    pub fn chain(&self) -> impl Iterator<Item = &Throwable> {
+        std::iter::successors(Some(self), |t| t.cause.as_deref())
+    }
+    /// This throwable and its frames only, without its causes or suppressed
+    /// throwables
+    ///
+    /// Use [`ThrowableRenderer::new`] to render the full chain as logback
+    /// would.
+    pub fn format_trace(&self) -> String {
+        ThrowableRenderer::new()
+            .suppressed(false)
+            .causes(false)
+            .render(self)
+    }
+    /// Total number of frames in this throwable and its chain of causes.
+    ///
+    /// Frames a cause shares with the throwable it caused (its common frames)
+    /// are counted again for the cause. Suppressed throwables are not included.
+    pub fn total_frames(&self) -> usize {
+        self.chain().map(|t| t.stack_trace.len()).sum()
+    }
+    /// The fraction of the frames in this throwable and its chain of causes
+    /// (counted as for [`Throwable::total_frames`]) whose class is in one of
+    /// the given application packages
+    ///
+    /// A high ratio suggests the problem is in the application rather than a
+    /// library it uses. A throwable without frames has a ratio of 0.
+    pub fn app_frame_ratio(&self, prefixes: &[&str]) -> f32 {
+        let total = self.total_frames();
+        if total == 0 {
+            return 0.0;
+        }
+        let app = self
+            .chain()
+            .flat_map(|t| &t.stack_trace)
+            .filter(|f| f.ste.class_name().is_some_and(|c| in_packages(c, prefixes)))
+            .count();
+        app as f32 / total as f32
+    }
+    /// Render this throwable followed by its suppressed throwables and causes
+    ///
+    /// Nesting deeper than `max_depth` causes or suppressed throwables is
+    /// replaced by a note that the chain was truncated. The chain is rendered
+    /// without recursion so long chains can't overflow the stack.
+    pub fn format_chain(&self, max_depth: usize) -> String {
+        ThrowableRenderer::new().max_depth(max_depth).render(self)
+    }
+    /// The number of frames compared by [`Self::same_signature`]
+    pub const SIGNATURE_FRAMES: usize = 5;
+    /// Whether two throwables are likely to be the same problem, ie they are
+    /// the same class thrown from the same place, regardless of their messages
+    pub fn same_signature(&self, other: &Throwable) -> bool {
+        self.same_signature_with(other, Self::SIGNATURE_FRAMES)
+    }
+    /// Compare the class and the top `frames` frames of two throwables
+    pub fn same_signature_with(&self, other: &Throwable, frames: usize) -> bool {
+        fn location(f: &StackFrame) -> (&Option<String>, &Option<String>, &Option<String>, i32) {
+            (&f.declaring_class, &f.method_name, &f.file_name, f.line)
+        }
+        let depth = |t: &Throwable| t.stack_trace.len().min(frames);
+        self.class_name == other.class_name
+            && depth(self) == depth(other)
+            && (self.stack_trace.iter().zip(&other.stack_trace))
+                .take(frames)
+                .all(|(a, b)| location(&a.ste) == location(&b.ste))
+    }
+    /// The number of frames at the end of this trace that are shared with the
+    /// trace of the throwable this one caused
+    pub fn common_frames(&self) -> usize {
+        usize::try_from(self.common_frames).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+impl Throwable {
+    fn new(class_name: &str, frames: &[(&str, &str)]) -> Self {
+        Self {
+            class_name: class_name.into(),
+            message: None,
+            common_frames: 0,
+            cause: None,
+            suppressed: vec![],
+            stack_trace: frames
+                .iter()
+                .enumerate()
+                .map(|(i, (class, method))| StackTraceElement {
+                    ste: StackFrame::new(class, method, i as i32 + 1),
+                    cpd: None,
+                })
+                .collect(),
+        }
+    }
+    fn caused_by(mut self, cause: Throwable) -> Self {
+        self.cause = Some(Box::new(cause));
+        self
+    }
+}
+
+#[test]
+fn test_app_frame_ratio() {
+    let ex = Throwable::new(
+        "java.sql.SQLException",
+        &[
+            ("org.postgresql.Driver", "execute"),
+            ("com.zaxxer.hikari.Proxy", "execute"),
+            ("com.acme.orders.Repository", "save"),
+        ],
+    )
+    .caused_by(Throwable::new(
+        "java.net.SocketException",
+        &[("java.net.Socket", "read"), ("com.acme.Main", "main")],
+    ));
+    assert_eq!(ex.app_frame_ratio(&["com.acme"]), 0.4);
+    assert_eq!(
+        ex.app_frame_ratio(&["com.acme.orders", "org.postgresql"]),
+        0.4
+    );
+    assert_eq!(ex.app_frame_ratio(&["com.acm"]), 0.0);
+    assert_eq!(Throwable::new("a.Empty", &[]).app_frame_ratio(&["a"]), 0.0);
+}
+
+#[test]
+fn test_common_frames() {
+    let evt = read_event(include_bytes!("../tests/fixtures/exception.ser"));
+    let ex = evt.throwable.unwrap();
+    assert_eq!(ex.common_frames(), 0);
+    assert_eq!(ex.cause.as_ref().unwrap().common_frames(), 2);
+}
+
+impl Drop for Throwable {
+    // Dropping a long chain of causes recursively can overflow the stack
+    fn drop(&mut self) {
+        let mut next = self.cause.take();
+        while let Some(mut cause) = next {
+            next = cause.cause.take();
+        }
+    }
+}
+
+#[test]
+fn test_chain() {
+    let ex = Throwable::new("a.Top", &[])
+        .caused_by(Throwable::new("a.Middle", &[]).caused_by(Throwable::new("a.Bottom", &[])));
+    assert_eq!(ex.chain().count(), 3);
+    let classes = ex.chain().map(Throwable::class_name).collect::<Vec<_>>();
+    assert_eq!(classes, ["a.Top", "a.Middle", "a.Bottom"]);
+    assert_eq!(Throwable::new("a.Top", &[]).chain().count(), 1);
+}
+
+#[test]
+fn test_format_chain() {
+    let mut ex = Throwable::new("a.Wrapper", &[("a.Main", "main")])
+        .caused_by(Throwable::new("a.Root", &[("a.Reader", "read")]));
+    ex.suppressed
+        .push(Throwable::new("a.Close", &[("a.Reader", "close")]));
+    assert_eq!(
+        ex.format_chain(10),
+        "a.Wrapper\n     at a.Main.main(Main.java:1)\n\
+         \tSuppressed: a.Close\n\t     at a.Reader.close(Reader.java:1)\n\
+         Caused by: a.Root\n     at a.Reader.read(Reader.java:1)"
+    );
+    assert_eq!(
+        ex.format_chain(0),
+        "a.Wrapper\n     at a.Main.main(Main.java:1)\n\
+         \t... chain truncated at depth 0\n\
+         ... chain truncated at depth 0"
+    );
+}
+
+#[test]
+fn test_stack() {
+    let mut evt = LogEvent::new(LogLevel::Error, "a.Main", "failed", &[]);
+    assert_eq!(evt.stack(), "");
+    evt.throwable = Some(Throwable::new(
+        "a.Error",
+        &[("a.Main", "run"), ("a.Main", "main")],
+    ));
+    assert_eq!(
+        evt.stack(),
+        "\na.Error\n     at a.Main.run(Main.java:1)\n     at a.Main.main(Main.java:2)"
+    );
+}
+
+#[test]
+fn test_first_line() {
+    let mut evt = LogEvent::new(LogLevel::Error, "a.Main", "failed", &[]);
+    assert_eq!(evt.exception_headline(), None);
+    let mut ex = Throwable::new("java.lang.NullPointerException", &[("a.Main", "run")]);
+    assert_eq!(ex.first_line(), "java.lang.NullPointerException");
+    ex.message = Some("Disk full".into());
+    assert_eq!(ex.first_line(), "java.lang.NullPointerException: Disk full");
+    evt.throwable = Some(ex);
+    assert_eq!(
+        evt.exception_headline().as_deref(),
+        Some("java.lang.NullPointerException: Disk full")
+    );
+
+    let evt = read_event(include_bytes!("../tests/fixtures/exception.ser"));
+    assert_eq!(
+        evt.exception_headline().as_deref(),
+        Some("java.lang.IllegalStateException: Could not load config")
+    );
+}
+
+#[test]
+fn test_deep_chain() {
+    let mut ex = Throwable::new("a.Root", &[("a.Main", "main")]);
+    for _ in 0..10_000 {
+        ex = Throwable::new("a.Wrapper", &[("a.Main", "main")]).caused_by(ex);
     }
+    assert_eq!(ex.total_frames(), 10_001);
+    assert_eq!(ex.root_cause().class_name(), "a.Root");
+    let trace = ex.format_chain(100);
+    assert_eq!(trace.matches("Caused by: ").count(), 100);
+    assert!(trace.ends_with("... chain truncated at depth 100"));
+    assert_eq!(
+        ex.format_chain(usize::MAX).matches("Caused by: ").count(),
+        10_000
+    );
+}
+
+#[test]
+fn test_same_signature() {
+    let frames = [("a.Dao", "find"), ("a.Service", "load"), ("a.Main", "main")];
+    let mut first = Throwable::new("java.lang.IllegalStateException", &frames);
+    first.message = Some("No user 'abc'".into());
+    let mut second = Throwable::new("java.lang.IllegalStateException", &frames[..2]);
+    second.message = Some("No user 'def'".into());
+    assert!(!first.same_signature(&second));
+    assert!(first.same_signature_with(&second, 2));
+
+    let second = Throwable::new("java.lang.IllegalStateException", &frames);
+    assert!(first.same_signature(&second));
+    let other = Throwable::new("java.lang.IllegalArgumentException", &frames);
+    assert!(!first.same_signature(&other));
+    let other = Throwable::new("java.lang.IllegalStateException", &frames[1..]);
+    assert!(!first.same_signature_with(&other, 1));
+}
+
+#[test]
+fn test_total_frames() {
+    let root = Throwable::new(
+        "java.io.IOException",
+        &[("a.Reader", "read"), ("a.Service", "load")],
+    );
+    let ex = Throwable::new(
+        "java.lang.RuntimeException",
+        &[("a.Service", "load"), ("a.Main", "run"), ("a.Main", "main")],
+    );
+    assert_eq!(ex.total_frames(), 3);
+    assert_eq!(ex.caused_by(root).total_frames(), 5);
+}
+
+#[test]
+fn test_root_cause() {
+    let ex = Throwable::new("java.lang.RuntimeException", &[]).caused_by(
+        Throwable::new("java.io.UncheckedIOException", &[])
+            .caused_by(Throwable::new("java.io.IOException", &[])),
+    );
+    assert_eq!(ex.root_cause().class_name(), "java.io.IOException");
+
+    let ex = Throwable::new("java.lang.RuntimeException", &[]);
+    assert_eq!(ex.root_cause().class_name(), "java.lang.RuntimeException");
 }
 
+#[allow(dead_code)]
 #[derive(Debug, FromJava)]
 #[jaded(rename)]
 pub struct StackFrame {
@@ -212,14 +1911,114 @@ pub struct StackFrame {
     file_name: Option<String>,
 }
 
-#[derive(Debug, FromJava)]
+impl StackFrame {
+    pub fn class_name(&self) -> Option<&str> {
+        self.declaring_class.as_deref()
+    }
+    pub fn method_name(&self) -> Option<&str> {
+        self.method_name.as_deref()
+    }
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+    pub fn line(&self) -> i32 {
+        self.line
+    }
+    pub fn class_loader_name(&self) -> Option<&str> {
+        self.class_loader_name.as_deref()
+    }
+    /// The name and version of the module the class is in, if it is in a
+    /// named module (JDK 9 and later)
+    pub fn module(&self) -> Option<(&str, Option<&str>)> {
+        let name = self
+            .module_name
+            .as_deref()
+            .filter(|name| !name.is_empty())?;
+        let version = self.module_version.as_deref().filter(|v| !v.is_empty());
+        Some((name, version))
+    }
+}
+
+#[cfg(test)]
+impl StackFrame {
+    fn new(class: &str, method: &str, line: i32) -> Self {
+        Self {
+            declaring_class: Some(class.into()),
+            line,
+            class_loader_name: None,
+            method_name: Some(method.into()),
+            module_name: None,
+            format: 0,
+            module_version: None,
+            file_name: class.rsplit('.').next().map(|c| format!("{c}.java")),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct StackTraceElement {
     ste: StackFrame,
     cpd: Option<ClassPackagingData>,
 }
 
+// Implemented by hand as the packaging data field is missing entirely (rather
+// than null) from the proxies written by some versions of logback
+impl FromJava for StackTraceElement {
+    fn from_value(value: &jaded::Value) -> jaded::ConversionResult<Self> {
+        let data = match value {
+            jaded::Value::Object(data) => data,
+            jaded::Value::Null => return Err(jaded::ConversionError::NullPointerException),
+            _ => {
+                return Err(jaded::ConversionError::InvalidType(
+                    "StackTraceElementProxy",
+                ))
+            }
+        };
+        Ok(Self {
+            ste: data.get_field_as("ste")?,
+            cpd: converters::optional_field(data, "cpd")?,
+        })
+    }
+}
+
+impl StackFrame {
+    /// Set in the format flags if the class loader is one of the JDK's built
+    /// in loaders, in which case its name is left out of traces
+    const BUILTIN_CLASS_LOADER: u8 = 0x1;
+    /// Set in the format flags if the module is one of the JDK's own, in which
+    /// case its version is left out of traces
+    const JDK_NON_UPGRADEABLE_MODULE: u8 = 0x2;
+
+    /// The name of the class loader, if it should be included in traces
+    fn loader_name(&self) -> Option<&str> {
+        match &self.class_loader_name {
+            _ if self.format & Self::BUILTIN_CLASS_LOADER != 0 => None,
+            Some(name) if !name.is_empty() => Some(name),
+            _ => None,
+        }
+    }
+}
+
 impl Display for StackTraceElement {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Matches the JDK's rendering, eg `loader/module@version/class.method(...)`
+        // or `loader//class.method(...)` when the class is not in a named module
+        let loader = self.ste.loader_name();
+        if let Some(loader) = loader {
+            write!(fmt, "{loader}/")?;
+        }
+        match self.ste.module() {
+            Some((name, version)) => {
+                fmt.write_str(name)?;
+                let jdk_module = self.ste.format & StackFrame::JDK_NON_UPGRADEABLE_MODULE != 0;
+                if let Some(version) = version.filter(|_| !jdk_module) {
+                    write!(fmt, "@{version}")?;
+                }
+                fmt.write_str("/")?;
+            }
+            None if loader.is_some() => fmt.write_str("/")?,
+            None => {}
+        }
         write!(
             fmt,
             "{}.{}({}:{})",
@@ -231,20 +2030,166 @@ impl Display for StackTraceElement {
     }
 }
 
+#[test]
+fn test_packaging_data() {
+    use crate::ThrowableRenderer;
+    let evt = read_event(include_bytes!("../tests/fixtures/packaging_data.ser"));
+    let renderer = ThrowableRenderer::new().packaging_data(true);
+    assert_eq!(
+        renderer.render(evt.throwable.as_ref().unwrap()),
+        "java.lang.IllegalStateException: With packaging data\n     \
+         at com.example.Service.load(Service.java:42) [service.jar:1.4.2]\n     \
+         at com.example.Main.main(Main.java:8) ~[app.jar:na]"
+    );
+
+    // Older proxies have no packaging data field at all
+    let evt = read_event(include_bytes!("../tests/fixtures/no_packaging_data.ser"));
+    let ex = evt.throwable.as_ref().unwrap();
+    assert_eq!(ex.stack_trace.len(), 2);
+    assert!(ex.stack_trace.iter().all(|ste| ste.cpd.is_none()));
+    assert_eq!(
+        renderer.render(ex),
+        "java.lang.IllegalStateException: No packaging data\n     \
+         at com.example.Service.load(Service.java:42)\n     \
+         at com.example.Main.main(Main.java:8)"
+    );
+}
+
+#[test]
+fn test_class_loader_rendering() {
+    let mut ste = StackTraceElement {
+        ste: StackFrame::new("com.acme.Main", "run", 3),
+        cpd: None,
+    };
+    assert_eq!(ste.to_string(), "com.acme.Main.run(Main.java:3)");
+    ste.ste.class_loader_name = Some("com.acme.PluginLoader".into());
+    assert_eq!(
+        ste.to_string(),
+        "com.acme.PluginLoader//com.acme.Main.run(Main.java:3)"
+    );
+    ste.ste.class_loader_name = Some("app".into());
+    ste.ste.format = StackFrame::BUILTIN_CLASS_LOADER;
+    assert_eq!(ste.to_string(), "com.acme.Main.run(Main.java:3)");
+}
+
+#[test]
+fn test_module_rendering() {
+    let mut ste = StackTraceElement {
+        ste: StackFrame::new("java.lang.Thread", "run", 833),
+        cpd: None,
+    };
+    assert_eq!(ste.ste.module(), None);
+    assert_eq!(ste.to_string(), "java.lang.Thread.run(Thread.java:833)");
+
+    ste.ste.module_name = Some("java.base".into());
+    ste.ste.module_version = Some("17.0.1".into());
+    assert_eq!(ste.ste.module(), Some(("java.base", Some("17.0.1"))));
+    assert_eq!(
+        ste.to_string(),
+        "java.base@17.0.1/java.lang.Thread.run(Thread.java:833)"
+    );
+    ste.ste.format = StackFrame::JDK_NON_UPGRADEABLE_MODULE;
+    assert_eq!(
+        ste.to_string(),
+        "java.base/java.lang.Thread.run(Thread.java:833)"
+    );
+
+    ste.ste.format = 0;
+    ste.ste.module_version = None;
+    ste.ste.class_loader_name = Some("plugins".into());
+    assert_eq!(ste.ste.module(), Some(("java.base", None)));
+    assert_eq!(
+        ste.to_string(),
+        "plugins/java.base/java.lang.Thread.run(Thread.java:833)"
+    );
+}
+
 #[derive(Debug, FromJava)]
+#[jaded(rename)]
 pub struct ClassPackagingData {
     code_location: String,
     version: String,
     exact: bool,
 }
 
-#[derive(Debug, FromJava)]
+#[allow(dead_code)]
+#[derive(Debug, Clone, FromJava)]
 pub struct Marker {
     name: String,
     #[jaded(field = "referenceList", from = "Markers")]
     references: Vec<Marker>,
 }
 
+impl Marker {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Whether this marker or any of the markers it references has the given
+    /// name
+    pub fn contains(&self, name: &str) -> bool {
+        self.iter().any(|m| m.name == name)
+    }
+    /// This marker followed by every marker it references, depth first
+    pub fn iter(&self) -> impl Iterator<Item = &Marker> {
+        let mut pending = vec![self];
+        std::iter::from_fn(move || {
+            let next = pending.pop()?;
+            pending.extend(next.references.iter().rev());
+            Some(next)
+        })
+    }
+}
+
+#[cfg(test)]
+impl Marker {
+    fn new(name: &str, references: Vec<Marker>) -> Self {
+        Self {
+            name: name.into(),
+            references,
+        }
+    }
+}
+
+#[test]
+fn test_marker_contains() {
+    let marker = Marker::new(
+        "AUDIT",
+        vec![Marker::new("SECURITY", vec![Marker::new("LOGIN", vec![])])],
+    );
+    assert_eq!(marker.name(), "AUDIT");
+    assert!(marker.contains("AUDIT"));
+    assert!(marker.contains("LOGIN"));
+    assert!(!marker.contains("BILLING"));
+
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Auth", "login", &[]);
+    assert!(!evt.has_marker("AUDIT"));
+    evt.marker = Some(marker);
+    assert!(evt.has_marker("AUDIT"));
+    assert!(evt.has_marker("SECURITY"));
+    assert!(!evt.has_marker("BILLING"));
+}
+
+#[test]
+fn test_marker_iter() {
+    let marker = Marker::new(
+        "A",
+        vec![
+            Marker::new(
+                "B",
+                vec![Marker::new("C", vec![]), Marker::new("D", vec![])],
+            ),
+            Marker::new("E", vec![Marker::new("F", vec![])]),
+        ],
+    );
+    let names = marker.iter().map(Marker::name).collect::<Vec<_>>();
+    assert_eq!(names, ["A", "B", "C", "D", "E", "F"]);
+
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    assert_eq!(evt.markers().count(), 0);
+    evt.marker = Some(marker);
+    assert_eq!(evt.markers().count(), 6);
+}
+
 #[derive(Debug, FromJava)]
 pub struct Markers {
     #[jaded(extract(converters::read_list))]
@@ -257,7 +2202,17 @@ impl From<Markers> for Vec<Marker> {
     }
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
+/// The level of an event
+///
+/// Levels are ordered from `Trace` to `Error`, with `Unknown` (any level that
+/// isn't one of logback's standard ones) above `Error` so that thresholds keep
+/// events with nonstandard levels.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "UPPERCASE")
+)]
 pub enum LogLevel {
     Trace,
     Debug,
@@ -268,6 +2223,25 @@ pub enum LogLevel {
 }
 
 impl LogLevel {
+    /// The integer codes logback uses for each level
+    pub const TRACE_INT: i32 = 5_000;
+    pub const DEBUG_INT: i32 = 10_000;
+    pub const INFO_INT: i32 = 20_000;
+    pub const WARN_INT: i32 = 30_000;
+    pub const ERROR_INT: i32 = 40_000;
+
+    /// The integer code logback uses for this level, if it is a standard one
+    pub fn to_i32(&self) -> Option<i32> {
+        use LogLevel::*;
+        Some(match self {
+            Trace => Self::TRACE_INT,
+            Debug => Self::DEBUG_INT,
+            Info => Self::INFO_INT,
+            Warn => Self::WARN_INT,
+            Error => Self::ERROR_INT,
+            Unknown => return None,
+        })
+    }
     pub fn name(&self) -> &'static str {
         use LogLevel::*;
         match self {
@@ -281,28 +2255,181 @@ impl LogLevel {
     }
 }
 
+impl LogLevel {
+    /// The syslog severity for this level, eg 3 (error) for `Error`. Unknown
+    /// levels are treated as debug.
+    pub fn syslog_severity(&self) -> u8 {
+        match self {
+            LogLevel::Error => 3,
+            LogLevel::Warn => 4,
+            LogLevel::Info => 6,
+            LogLevel::Debug | LogLevel::Trace | LogLevel::Unknown => 7,
+        }
+    }
+    /// The numeric level used by Bunyan, eg 50 for `Error`. Unknown levels
+    /// are treated as debug.
+    pub fn bunyan_level(&self) -> u8 {
+        match self {
+            LogLevel::Error => 50,
+            LogLevel::Warn => 40,
+            LogLevel::Info => 30,
+            LogLevel::Debug | LogLevel::Unknown => 20,
+            LogLevel::Trace => 10,
+        }
+    }
+    /// The closest Windows event log type for this level. Unknown levels are
+    /// treated as informational.
+    pub fn windows_severity(&self) -> WindowsSeverity {
+        match self {
+            LogLevel::Error => WindowsSeverity::Error,
+            LogLevel::Warn => WindowsSeverity::Warning,
+            _ => WindowsSeverity::Information,
+        }
+    }
+}
+
+/// The event types used by the Windows event log. Casting to `u16` gives the
+/// `EVENTLOG_*_TYPE` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum WindowsSeverity {
+    Error = 1,
+    Warning = 2,
+    Information = 4,
+}
+
+#[test]
+fn test_syslog_severity() {
+    use LogLevel::*;
+    let severities = [Trace, Debug, Info, Warn, Error, Unknown].map(|l| l.syslog_severity());
+    assert_eq!(severities, [7, 7, 6, 4, 3, 7]);
+}
+
+#[test]
+fn test_bunyan_level() {
+    use LogLevel::*;
+    let levels = [Trace, Debug, Info, Warn, Error, Unknown].map(|l| l.bunyan_level());
+    assert_eq!(levels, [10, 20, 30, 40, 50, 20]);
+}
+
+#[test]
+fn test_windows_severity() {
+    use LogLevel::*;
+    let severities =
+        [Trace, Debug, Info, Warn, Error, Unknown].map(|l| l.windows_severity() as u16);
+    assert_eq!(severities, [4, 4, 4, 2, 1, 4]);
+}
+
+impl LogLevel {
+    /// The label for this level from a set of custom labels
+    pub fn label<'l>(&self, labels: &'l LevelLabels) -> &'l str {
+        &labels.0[*self as usize]
+    }
+}
+
+/// Names to use for levels when rendering events, eg to match the names
+/// expected by another system
+///
+/// The default labels are the standard uppercase names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelLabels([Cow<'static, str>; 6]);
+
+impl Default for LevelLabels {
+    fn default() -> Self {
+        use LogLevel::*;
+        Self([Trace, Debug, Info, Warn, Error, Unknown].map(|l| Cow::Borrowed(l.name())))
+    }
+}
+
+impl LevelLabels {
+    /// The standard names in lowercase, eg `info`, `warn`
+    pub fn lowercase() -> Self {
+        Self(Self::default().0.map(|l| Cow::Owned(l.to_lowercase())))
+    }
+    /// Replace the label for a single level
+    pub fn with(mut self, level: LogLevel, label: impl Into<String>) -> Self {
+        self.0[level as usize] = Cow::Owned(label.into());
+        self
+    }
+}
+
+#[test]
+fn test_level_labels() {
+    let labels = LevelLabels::default();
+    assert_eq!(LogLevel::Warn.label(&labels), "WARN");
+    let labels = LevelLabels::lowercase().with(LogLevel::Warn, "WARNING");
+    assert_eq!(LogLevel::Info.label(&labels), "info");
+    assert_eq!(LogLevel::Warn.label(&labels), "WARNING");
+    assert_eq!(LogLevel::Warn.name(), "WARN");
+}
+
 impl Display for LogLevel {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), FmtError> {
-        fmt.write_str(self.name())
+        fmt.pad(self.name())
     }
 }
 
+#[test]
+fn test_level_padding() {
+    assert_eq!(format!("{:<5}|", LogLevel::Info), "INFO |");
+    assert_eq!(format!("{:>5}|", LogLevel::Warn), " WARN|");
+    assert_eq!(format!("{:<5}|", LogLevel::Error), "ERROR|");
+    assert_eq!(format!("{}|", LogLevel::Debug), "DEBUG|");
+}
+
 impl From<i32> for LogLevel {
     fn from(value: i32) -> Self {
         match value {
-            5_000 => Self::Trace,
-            10_000 => Self::Debug,
-            20_000 => Self::Info,
-            30_000 => Self::Warn,
-            40_000 => Self::Error,
+            Self::TRACE_INT => Self::Trace,
+            Self::DEBUG_INT => Self::Debug,
+            Self::INFO_INT => Self::Info,
+            Self::WARN_INT => Self::Warn,
+            Self::ERROR_INT => Self::Error,
             _ => Self::Unknown,
         }
     }
 }
 
+#[test]
+fn test_level_codes() {
+    assert_eq!(LogLevel::from(LogLevel::TRACE_INT), LogLevel::Trace);
+    assert_eq!(LogLevel::from(LogLevel::ERROR_INT), LogLevel::Error);
+    assert_eq!(LogLevel::from(12_345), LogLevel::Unknown);
+    for level in [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ] {
+        assert_eq!(LogLevel::from(level.to_i32().unwrap()), level);
+    }
+    assert_eq!(LogLevel::Unknown.to_i32(), None);
+}
+
+#[test]
+fn test_level_from_code_str() {
+    assert_eq!("20000".parse::<LogLevel>().unwrap(), LogLevel::Info);
+    assert_eq!("40000".parse::<LogLevel>().unwrap(), LogLevel::Error);
+    assert!(matches!(
+        "12345".parse::<LogLevel>(),
+        Err(Error::UnknownLogLevel(code)) if code == "12345"
+    ));
+    assert!("99999999999".parse::<LogLevel>().is_err());
+}
+
+/// Parse a level from its name, the first letter of its name or its numeric
+/// code, eg `"20000"` for `Info`. Codes that are not one of logback's levels
+/// are rejected rather than read as [`LogLevel::Unknown`].
 impl FromStr for LogLevel {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(code) = s.parse::<i32>() {
+            return match Self::from(code) {
+                Self::Unknown => Err(Error::UnknownLogLevel(s.into())),
+                level => Ok(level),
+            };
+        }
         Ok(match s.to_lowercase().as_str() {
             "t" | "trace" => Self::Trace,
             "d" | "debug" => Self::Debug,
@@ -314,24 +2441,45 @@ impl FromStr for LogLevel {
     }
 }
 
-#[derive(Debug, FromJava)]
-struct PropertyMap {
-    #[jaded(extract(converters::read_map))]
-    pub values: HashMap<String, String>,
-}
-
-impl From<PropertyMap> for HashMap<String, String> {
-    fn from(value: PropertyMap) -> Self {
-        value.values
-    }
-}
-
-mod converters {
-    use jaded::{AnnotationIter, ConversionResult, FromJava};
+/// Functions for reading the data logback's classes write in their custom
+/// `writeObject` methods, for use with `#[jaded(extract(...))]`
+///
+/// ```
+/// use jaded::FromJava;
+/// use logback::converters::read_list;
+///
+/// /// A class that writes the number of tags followed by each tag
+/// #[derive(FromJava)]
+/// struct Tags {
+///     #[jaded(extract(read_list))]
+///     tags: Vec<String>,
+/// }
+/// ```
+pub mod converters {
+    use jaded::{AnnotationIter, ConversionError, ConversionResult, FromJava, ObjectData, Value};
     use std::{collections::HashMap, hash::Hash};
+    /// Read a single int, eg the level of an event
     pub fn read_i32(anno: &mut AnnotationIter) -> ConversionResult<i32> {
         anno.read_i32()
     }
+    /// Read the level of an event
+    ///
+    /// LoggingEventVO writes the level as an int after its other fields but
+    /// events from some configurations have a `level` field holding the
+    /// `ch.qos.logback.classic.Level` itself, in which case its `levelInt` is
+    /// used and nothing is read from the annotation.
+    pub fn read_level(data: &ObjectData, anno: &mut AnnotationIter) -> ConversionResult<i32> {
+        match data.get_field("level") {
+            None => anno.read_i32(),
+            Some(Value::Primitive(jaded::PrimitiveType::Int(level))) => Ok(*level),
+            Some(Value::Object(level)) => level.get_field_as("levelInt"),
+            Some(Value::Null) => Err(ConversionError::NullPointerException),
+            Some(_) => Err(ConversionError::InvalidType("int or Level")),
+        }
+    }
+    /// Read a count followed by that many objects, as written by `ArrayList`
+    ///
+    /// A negative count, used by logback for a null array, gives an empty list.
     pub fn read_list<T>(anno: &mut AnnotationIter) -> ConversionResult<Vec<T>>
     where
         T: FromJava,
@@ -340,6 +2488,7 @@ mod converters {
             .map(|_| anno.read_object_as())
             .collect()
     }
+    /// Read the buckets and entries written by a `HashMap`
     pub fn read_map<T, U>(anno: &mut AnnotationIter) -> ConversionResult<HashMap<T, U>>
     where
         T: FromJava + Eq + Hash,
@@ -354,6 +2503,56 @@ mod converters {
         Ok(map)
     }
 
+    /// Read a field that may not be present, eg if it was added in a later
+    /// version of a class
+    pub fn optional_field<T: FromJava>(
+        data: &ObjectData,
+        name: &str,
+    ) -> ConversionResult<Option<T>> {
+        match data.get_field(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => T::from_value(value).map(Some),
+        }
+    }
+
+    /// A string or boxed primitive converted to its text representation
+    pub struct Text(String);
+    impl FromJava for Text {
+        fn from_value(value: &Value) -> ConversionResult<Self> {
+            use jaded::PrimitiveType::*;
+            Ok(Self(match value {
+                Value::Null => "null".into(),
+                Value::JavaString(s) => s.clone(),
+                Value::Primitive(p) => match p {
+                    Byte(b) => b.to_string(),
+                    Char(c) => c.to_string(),
+                    Double(d) => d.to_string(),
+                    Float(f) => f.to_string(),
+                    Int(i) => i.to_string(),
+                    Long(l) => l.to_string(),
+                    Short(s) => s.to_string(),
+                    Boolean(b) => b.to_string(),
+                },
+                // Integer, Long, Boolean etc store their value in a single field
+                Value::Object(data) => match data.get_field("value") {
+                    Some(value @ Value::Primitive(_)) => return Self::from_value(value),
+                    _ => return Err(ConversionError::InvalidType("string or boxed primitive")),
+                },
+                _ => return Err(ConversionError::InvalidType("string or boxed primitive")),
+            }))
+        }
+    }
+    impl From<Text> for String {
+        fn from(text: Text) -> String {
+            text.0
+        }
+    }
+
+    /// Any of the map types used by logback for the MDC and context properties
+    ///
+    /// Maps shared between events (eg `Collections.emptyMap()`) are only
+    /// written once but the parser resolves the back-references so each event
+    /// sees the full map.
     #[derive(Debug, FromJava)]
     pub enum Map {
         #[jaded(class = "java.util.Collections$EmptyMap")]
@@ -376,45 +2575,109 @@ mod converters {
 
 #[test]
 fn test_format() {
-    assert_eq!(format("no anchors", &[]), Cow::Borrowed("no anchors"));
     assert_eq!(
-        format("single {} anchor", &["central".into()]),
+        LogEvent::format("no anchors", &[]),
+        Cow::Borrowed("no anchors")
+    );
+    assert_eq!(
+        LogEvent::format("single {} anchor", &["central".into()]),
         Cow::Owned::<str>("single central anchor".into())
     );
     assert_eq!(
-        format("unused arg", &["foo".into()]),
+        LogEvent::format("unused arg", &["foo".into()]),
         Cow::Borrowed("unused arg")
     );
     assert_eq!(
-        format("unused {} anchor", &[]),
+        LogEvent::format("unused {} anchor", &[]),
         Cow::Borrowed("unused {} anchor")
     );
     assert_eq!(
-        format(r"escaped escape \\{}", &["foo".into()]),
+        LogEvent::format(r"escaped escape \\{}", &["foo".into()]),
         Cow::Owned::<str>(r"escaped escape \\foo".into())
     );
     assert_eq!(
-        format(r"Partially escaped \{ anchor", &[]),
-        Cow::Borrowed(r"Partially escaped \{ anchor".into())
+        LogEvent::format(r"Partially escaped \{ anchor", &[]),
+        Cow::Borrowed(r"Partially escaped \{ anchor")
     );
     assert_eq!(
-        format(r"Partially escaped \{ anchor with {}", &["arg".into()]),
+        LogEvent::format(r"Partially escaped \{ anchor with {}", &["arg".into()]),
         Cow::Owned::<str>(r"Partially escaped \{ anchor with arg".into())
     );
     assert_eq!(
-        format(r"End with {} escape\", &["final".into()]),
+        LogEvent::format(r"End with {} escape\", &["final".into()]),
         Cow::Owned::<str>(r"End with final escape\".into())
     );
     assert_eq!(
-        format("Too {} arguments {}", &["few".into()]),
+        LogEvent::format("Too {} arguments {}", &["few".into()]),
         Cow::Borrowed("Too few arguments {}")
     );
     assert_eq!(
-        format("Too {} arguments", &["many".into(), "ignored".into()]),
+        LogEvent::format("Too {} arguments", &["many".into(), "ignored".into()]),
         Cow::Borrowed("Too many arguments")
     );
     assert_eq!(
-        format("Not {} an {anchor}", &["really".into()]),
+        LogEvent::format("Not {} an {anchor}", &["really".into()]),
         Cow::Owned::<str>("Not really an {anchor}".into())
     );
 }
+
+#[test]
+fn test_format_tail() {
+    let format = |args: &[&str]| {
+        let args = args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        LogEvent::format("{}, {} and {}", &args).into_owned()
+    };
+    assert_eq!(format(&["a"]), "a, {} and {}");
+    assert_eq!(format(&["a", "b"]), "a, b and {}");
+    assert_eq!(format(&["a", "b", "c"]), "a, b and c");
+    // As in logback, everything after the first unfilled anchor is copied
+    // as it is, including escapes
+    assert_eq!(
+        LogEvent::format(r"{} {} then \{} and \{}", &["a".into()]),
+        r"a {} then \{} and \{}"
+    );
+    assert_eq!(LogEvent::format(r"\{} {} {}", &["a".into()]), r"{} a {}");
+    // The last anchor having an argument leaves nothing to copy
+    assert_eq!(
+        LogEvent::format("ends with {}", &["a".into()]),
+        "ends with a"
+    );
+}
+
+#[test]
+fn test_format_missing_args() {
+    let args = ["one".to_string()];
+    let format = |on_missing_arg| {
+        LogEvent::format_with(r"{} and {} \{}", &args, &FormatOptions { on_missing_arg })
+            .into_owned()
+    };
+    assert_eq!(format(MissingArg::KeepAnchor), r"one and {} \{}");
+    assert_eq!(
+        format(MissingArg::Placeholder("<missing>".into())),
+        "one and <missing> {}"
+    );
+    assert_eq!(format(MissingArg::Empty), "one and  {}");
+
+    let options = FormatOptions {
+        on_missing_arg: MissingArg::Placeholder("?".into()),
+    };
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "{} of {}", &[]);
+    assert_eq!(evt.message_with(&options), "? of ?");
+    assert_eq!(evt.message(), "{} of {}");
+}
+
+#[test]
+fn test_format_borrows_unchanged_template() {
+    assert!(matches!(
+        LogEvent::format("unused arg", &["foo".into()]),
+        Cow::Borrowed(_)
+    ));
+    assert!(matches!(
+        LogEvent::format("Too {} arguments", &[]),
+        Cow::Borrowed(_)
+    ));
+    assert!(matches!(
+        LogEvent::format(r"escaped \{} anchor", &["foo".into()]),
+        Cow::Owned(_)
+    ));
+}
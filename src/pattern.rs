@@ -0,0 +1,694 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Display, Formatter},
+    str::FromStr,
+    sync::Arc,
+};
+
+use crate::{strip_ansi, LevelLabels, LineEnding, LogEvent, ThrowableRenderer};
+
+/// A layout for rendering events using a subset of logback's PatternLayout
+/// syntax, eg `%d %-5level [%thread] %logger{36} - %msg%n`
+///
+/// Supported conversion words are
+/// * `d`/`date` with an optional `SimpleDateFormat` style option
+///   (`yyyy`, `yy`, `MM`, `dd`, `HH`, `mm`, `ss` and `SSS`). Times are in UTC.
+/// * `r`/`relative` for the milliseconds since the logging context was
+///   created. This is empty if the event has no context.
+/// * `p`/`le`/`level`
+/// * `c`/`lo`/`logger` with an optional length to abbreviate the name to
+/// * `t`/`thread`
+/// * `m`/`msg`/`message`
+/// * `X`/`mdc` with an optional key. Without a key, all entries are included,
+///   sorted by key.
+/// * `ex`/`exception`/`throwable`
+/// * `marker`
+/// * `cn`/`contextName`
+/// * `n` for a new line
+///
+/// Other words can be added using a [`WordRegistry`] (see [`Pattern::parse_with`]).
+///
+/// Each conversion can be given a minimum width (`%5level`, or `%-5level` to
+/// pad on the right) and a maximum width (`%.10logger` to keep the last 10
+/// characters, or `%.-10logger` to keep the first 10). A literal `%` is written
+/// as `\%`.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    segments: Vec<Segment>,
+    labels: LevelLabels,
+    strip_ansi: bool,
+    line_ending: LineEnding,
+}
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Self, PatternError> {
+        Self::parse_with(pattern, &WordRegistry::default())
+    }
+
+    /// Parse a pattern that may use custom conversion words as well as the
+    /// standard ones. Standard words can't be replaced.
+    pub fn parse_with(pattern: &str, words: &WordRegistry) -> Result<Self, PatternError> {
+        Parser::new(pattern, words).parse()
+    }
+
+    /// Check that a pattern is valid without keeping the parsed result
+    pub fn validate(pattern: &str) -> Result<(), PatternError> {
+        Self::parse(pattern).map(|_| ())
+    }
+
+    /// Use custom names for levels instead of the standard uppercase ones
+    pub fn with_level_labels(mut self, labels: LevelLabels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Remove ANSI escape sequences from the values of conversions, eg colour
+    /// codes embedded in messages by the application. Literal text in the
+    /// pattern itself is left as it is.
+    pub fn with_strip_ansi(mut self) -> Self {
+        self.strip_ansi = true;
+        self
+    }
+
+    /// End lines with `\r\n` instead of `\n`. This applies to `%n` and to
+    /// new lines in messages and exceptions.
+    pub fn with_line_ending(mut self, ending: LineEnding) -> Self {
+        self.line_ending = ending;
+        self
+    }
+
+    pub(crate) fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Whether the pattern includes the event's exception (`%ex`)
+    ///
+    /// Logback adds the exception to the end of patterns that don't (see
+    /// [`run_tail`](crate::run_tail)).
+    pub fn renders_exceptions(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|seg| matches!(seg, Segment::Conversion(Word::Exception, _)))
+    }
+
+    pub fn render(&self, evt: &LogEvent) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Conversion(word, spec) => {
+                    let value = word.render(evt, &self.labels);
+                    if self.strip_ansi {
+                        spec.apply(&strip_ansi(&value), &mut out)
+                    } else {
+                        spec.apply(&value, &mut out)
+                    }
+                }
+            }
+        }
+        self.line_ending.convert_owned(out)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = PatternError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Custom conversion words that can be used in a pattern, eg for words
+/// provided by an application's own logback converters
+#[derive(Clone, Default)]
+pub struct WordRegistry {
+    words: HashMap<String, CustomWord>,
+}
+
+impl WordRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a conversion word rendered by the given function
+    pub fn register(
+        mut self,
+        word: &str,
+        render: impl Fn(&LogEvent) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.words.insert(word.into(), CustomWord(Arc::new(render)));
+        self
+    }
+}
+
+impl Debug for WordRegistry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_set().entries(self.words.keys()).finish()
+    }
+}
+
+#[derive(Clone)]
+struct CustomWord(Arc<dyn Fn(&LogEvent) -> String + Send + Sync>);
+
+impl Debug for CustomWord {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("CustomWord")
+    }
+}
+
+/// Problem found when parsing a pattern
+///
+/// The position is the byte offset into the pattern of the start of the
+/// conversion (or part of a conversion) that was invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    pub position: usize,
+    pub kind: PatternErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternErrorKind {
+    /// A `%` at the end of the pattern
+    MissingConversion,
+    UnknownConversion(String),
+    /// An option (`{...}`) that is not closed
+    UnterminatedOption,
+    /// A width specification that doesn't match `-?min.-?max`
+    InvalidWidth,
+    /// An option that isn't valid for its conversion word, eg a non-numeric
+    /// logger length
+    InvalidOption(String),
+}
+
+impl Display for PatternError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        use PatternErrorKind::*;
+        match &self.kind {
+            MissingConversion => write!(fmt, "Missing conversion word")?,
+            UnknownConversion(word) => write!(fmt, "Unknown conversion word: {word:?}")?,
+            UnterminatedOption => write!(fmt, "Unterminated option")?,
+            InvalidWidth => write!(fmt, "Invalid width specification")?,
+            InvalidOption(opt) => write!(fmt, "Invalid option: {opt:?}")?,
+        }
+        write!(fmt, " at position {}", self.position)
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Conversion(Word, FormatSpec),
+}
+
+#[derive(Debug, Clone)]
+enum Word {
+    Date(DateFormat),
+    Relative,
+    Level,
+    Logger(Option<usize>),
+    Thread,
+    Message,
+    Mdc(Option<String>),
+    Exception,
+    Marker,
+    ContextName,
+    Newline,
+    Custom(CustomWord),
+}
+
+impl Word {
+    fn new(
+        name: &str,
+        option: Option<&str>,
+        position: usize,
+        custom: &WordRegistry,
+    ) -> Result<Self, PatternError> {
+        let invalid = |opt: &str| PatternError {
+            position,
+            kind: PatternErrorKind::InvalidOption(opt.into()),
+        };
+        Ok(match name {
+            "d" | "date" => Word::Date(match option {
+                None | Some("ISO8601") => DateFormat::parse(DateFormat::ISO8601).unwrap(),
+                Some(fmt) => DateFormat::parse(fmt).ok_or_else(|| invalid(fmt))?,
+            }),
+            "r" | "relative" => Word::Relative,
+            "p" | "le" | "level" => Word::Level,
+            "c" | "lo" | "logger" => Word::Logger(match option {
+                None => None,
+                Some(len) => Some(len.trim().parse().map_err(|_| invalid(len))?),
+            }),
+            "t" | "thread" => Word::Thread,
+            "m" | "msg" | "message" => Word::Message,
+            "X" | "mdc" => Word::Mdc(option.map(String::from)),
+            "ex" | "exception" | "throwable" => Word::Exception,
+            "marker" => Word::Marker,
+            "cn" | "contextName" => Word::ContextName,
+            "n" => Word::Newline,
+            _ => match custom.words.get(name) {
+                Some(word) => Word::Custom(word.clone()),
+                None => {
+                    return Err(PatternError {
+                        position,
+                        kind: PatternErrorKind::UnknownConversion(name.into()),
+                    })
+                }
+            },
+        })
+    }
+
+    fn render(&self, evt: &LogEvent, labels: &LevelLabels) -> String {
+        match self {
+            Word::Date(fmt) => fmt.format(&evt.time()),
+            Word::Relative => evt
+                .relative_millis()
+                .map(|ms| ms.to_string())
+                .unwrap_or_default(),
+            Word::Level => evt.level.label(labels).into(),
+            Word::Logger(None) => evt.logger_name.0.clone(),
+            Word::Logger(Some(0)) => evt.logger_name.0.rsplit('.').next().unwrap().into(),
+            Word::Logger(Some(len)) => evt.logger_name.reduced(*len).into_owned(),
+            Word::Thread => evt.thread_name.clone(),
+            Word::Message => evt.message().into_owned(),
+            Word::Mdc(Some(key)) => evt.mdc.get(key).cloned().unwrap_or_default(),
+            Word::Mdc(None) => evt
+                .mdc_sorted()
+                .into_iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Word::Exception => evt
+                .throwable
+                .as_ref()
+                .map(|t| ThrowableRenderer::new().render(t))
+                .unwrap_or_default(),
+            Word::Marker => evt
+                .marker
+                .as_ref()
+                .map(|m| m.name.clone())
+                .unwrap_or_default(),
+            Word::ContextName => evt.context().name.clone(),
+            Word::Newline => "\n".into(),
+            Word::Custom(render) => (render.0)(evt),
+        }
+    }
+}
+
+/// Minimum and maximum widths of a conversion
+#[derive(Debug, Clone, Default)]
+struct FormatSpec {
+    min: Option<usize>,
+    pad_right: bool,
+    max: Option<usize>,
+    keep_start: bool,
+}
+
+impl FormatSpec {
+    fn apply(&self, text: &str, out: &mut String) {
+        let len = text.chars().count();
+        let text = match self.max {
+            Some(max) if len > max && self.keep_start => {
+                &text[..text.char_indices().nth(max).map_or(text.len(), |(i, _)| i)]
+            }
+            Some(max) if len > max => &text[text.char_indices().nth(len - max).unwrap().0..],
+            _ => text,
+        };
+        let padding = self
+            .min
+            .map_or(0, |min| min.saturating_sub(text.chars().count()));
+        if !self.pad_right {
+            out.extend(std::iter::repeat_n(' ', padding));
+        }
+        out.push_str(text);
+        if self.pad_right {
+            out.extend(std::iter::repeat_n(' ', padding));
+        }
+    }
+}
+
+/// Subset of Java's SimpleDateFormat
+#[derive(Debug, Clone)]
+pub(crate) struct DateFormat(Vec<DateItem>);
+
+#[derive(Debug, Clone)]
+enum DateItem {
+    Literal(String),
+    Year(usize),
+    Month(usize),
+    Day(usize),
+    Hour(usize),
+    Minute(usize),
+    Second(usize),
+    Millis(usize),
+}
+
+impl DateFormat {
+    const ISO8601: &'static str = "yyyy-MM-dd HH:mm:ss,SSS";
+
+    /// Parse a date format, returning None if it contains unsupported fields
+    pub(crate) fn parse(fmt: &str) -> Option<Self> {
+        let mut items = vec![];
+        let mut chars = fmt.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                'y' | 'M' | 'd' | 'H' | 'm' | 's' | 'S' => {
+                    let mut width = 1;
+                    while chars.next_if_eq(&c).is_some() {
+                        width += 1;
+                    }
+                    items.push(match c {
+                        'y' => DateItem::Year(width),
+                        'M' => DateItem::Month(width),
+                        'd' => DateItem::Day(width),
+                        'H' => DateItem::Hour(width),
+                        'm' => DateItem::Minute(width),
+                        's' => DateItem::Second(width),
+                        _ => DateItem::Millis(width),
+                    });
+                }
+                '\'' => {
+                    let mut text = String::new();
+                    loop {
+                        match chars.next()? {
+                            '\'' if chars.next_if_eq(&'\'').is_none() => break,
+                            c => text.push(c),
+                        }
+                    }
+                    items.push(DateItem::Literal(text));
+                }
+                c if c.is_ascii_alphabetic() => return None,
+                c => items.push(DateItem::Literal(c.into())),
+            }
+        }
+        Some(Self(items))
+    }
+
+    pub(crate) fn format(&self, time: &time::OffsetDateTime) -> String {
+        let mut out = String::new();
+        for item in &self.0 {
+            match item {
+                DateItem::Literal(text) => out.push_str(text),
+                DateItem::Year(2) => out.push_str(&format!("{:02}", time.year() % 100)),
+                DateItem::Year(w) => out.push_str(&format!("{:0w$}", time.year())),
+                DateItem::Month(w) => out.push_str(&format!("{:0w$}", time.month() as u8)),
+                DateItem::Day(w) => out.push_str(&format!("{:0w$}", time.day())),
+                DateItem::Hour(w) => out.push_str(&format!("{:0w$}", time.hour())),
+                DateItem::Minute(w) => out.push_str(&format!("{:0w$}", time.minute())),
+                DateItem::Second(w) => out.push_str(&format!("{:0w$}", time.second())),
+                DateItem::Millis(w) => out.push_str(&format!("{:0w$}", time.millisecond())),
+            }
+        }
+        out
+    }
+}
+
+struct Parser<'p> {
+    pattern: &'p str,
+    chars: std::iter::Peekable<std::str::CharIndices<'p>>,
+    words: &'p WordRegistry,
+}
+
+impl<'p> Parser<'p> {
+    fn new(pattern: &'p str, words: &'p WordRegistry) -> Self {
+        Self {
+            pattern,
+            chars: pattern.char_indices().peekable(),
+            words,
+        }
+    }
+
+    fn parse(mut self) -> Result<Pattern, PatternError> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        while let Some((i, c)) = self.chars.next() {
+            match c {
+                '\\' if self.chars.next_if(|&(_, c)| c == '%').is_some() => literal.push('%'),
+                '%' => {
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(self.conversion(i)?);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Pattern {
+            segments,
+            labels: LevelLabels::default(),
+            strip_ansi: false,
+            line_ending: LineEnding::Lf,
+        })
+    }
+
+    /// Parse a conversion following a '%' at the given position
+    fn conversion(&mut self, start: usize) -> Result<Segment, PatternError> {
+        let spec = self.format_spec()?;
+        let word_start = self.position();
+        let word = self.take_while(|c| c.is_ascii_alphanumeric());
+        if word.is_empty() {
+            return Err(PatternError {
+                position: start,
+                kind: PatternErrorKind::MissingConversion,
+            });
+        }
+        let option = match self.chars.next_if(|&(_, c)| c == '{') {
+            Some((open, _)) => {
+                let option = self.take_while(|c| c != '}');
+                if self.chars.next().is_none() {
+                    return Err(PatternError {
+                        position: open,
+                        kind: PatternErrorKind::UnterminatedOption,
+                    });
+                }
+                Some(option)
+            }
+            None => None,
+        };
+        Ok(Segment::Conversion(
+            Word::new(word, option, word_start, self.words)?,
+            spec,
+        ))
+    }
+
+    fn format_spec(&mut self) -> Result<FormatSpec, PatternError> {
+        let start = self.position();
+        let invalid = PatternError {
+            position: start,
+            kind: PatternErrorKind::InvalidWidth,
+        };
+        let mut spec = FormatSpec {
+            pad_right: self.chars.next_if(|&(_, c)| c == '-').is_some(),
+            ..Default::default()
+        };
+        spec.min = self.number();
+        if spec.pad_right && spec.min.is_none() {
+            return Err(invalid);
+        }
+        if self.chars.next_if(|&(_, c)| c == '.').is_some() {
+            spec.keep_start = self.chars.next_if(|&(_, c)| c == '-').is_some();
+            spec.max = Some(self.number().ok_or(invalid)?);
+        }
+        Ok(spec)
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        self.take_while(|c| c.is_ascii_digit()).parse().ok()
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'p str {
+        let start = self.position();
+        while self.chars.next_if(|&(_, c)| pred(c)).is_some() {}
+        &self.pattern[start..self.position()]
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars.peek().map_or(self.pattern.len(), |&(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+use crate::LogLevel;
+
+#[test]
+fn test_render() {
+    let mut evt = LogEvent::new(
+        LogLevel::Warn,
+        "uk.ac.diamond.daq.persistence.jythonshelf",
+        "Ran {} of {}",
+        &["3", "4"],
+    );
+    evt.time_stamp = 1_689_000_001_500;
+    evt.mdc.insert("user".into(), "abc".into());
+    let pattern =
+        Pattern::parse(r"%d [%thread] %-5level %logger{20} - %msg user=%X{user} \%%n").unwrap();
+    assert_eq!(
+        pattern.render(&evt),
+        "2023-07-10 14:40:01,500 [main] WARN  u.a.d.d.p.jythonshelf - Ran 3 of 4 user=abc %\n"
+    );
+}
+
+#[test]
+fn test_render_exception() {
+    let mut evt = LogEvent::new(LogLevel::Error, "a.Main", "Failed", &[]);
+    let with_ex = Pattern::parse("%msg%n%ex").unwrap();
+    let without_ex = Pattern::parse("%msg").unwrap();
+    assert_eq!(with_ex.render(&evt), "Failed\n");
+    evt.throwable = Some(
+        crate::Throwable::new("java.io.IOException", &[("a.Main", "main")])
+            .caused_by(crate::Throwable::new("a.DiskFull", &[("a.Disk", "write")])),
+    );
+    assert_eq!(with_ex.render(&evt), evt.message_with_exception());
+    assert!(with_ex
+        .render(&evt)
+        .ends_with("Caused by: a.DiskFull\n     at a.Disk.write(Disk.java:1)"));
+    assert_eq!(without_ex.render(&evt), "Failed");
+}
+
+#[test]
+fn test_render_relative() {
+    let pattern = Pattern::parse("[%6r] %msg").unwrap();
+    let evt = crate::read_event(include_bytes!("../tests/fixtures/exception.ser"));
+    assert_eq!(pattern.render(&evt), "[  1500] Failed to start service");
+    let evt = crate::read_event(include_bytes!("../tests/fixtures/null_context.ser"));
+    assert_eq!(pattern.render(&evt), "[      ] No context");
+}
+
+#[test]
+fn test_render_level_labels() {
+    let labels = LevelLabels::lowercase().with(LogLevel::Warn, "WARNING");
+    let pattern = Pattern::parse("%-7level|")
+        .unwrap()
+        .with_level_labels(labels);
+    let info = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    let warn = LogEvent::new(LogLevel::Warn, "a.Main", "", &[]);
+    assert_eq!(pattern.render(&info), "info   |");
+    assert_eq!(pattern.render(&warn), "WARNING|");
+}
+
+#[test]
+fn test_render_line_endings() {
+    let mut evt = LogEvent::new(LogLevel::Error, "a.Main", "failed", &[]);
+    evt.throwable = Some(crate::Throwable::new("a.Error", &[("a.Main", "run")]));
+    let pattern = Pattern::parse("%m%n%ex").unwrap();
+    assert_eq!(
+        pattern.render(&evt),
+        "failed\na.Error\n     at a.Main.run(Main.java:1)"
+    );
+    assert_eq!(
+        pattern.with_line_ending(LineEnding::CrLf).render(&evt),
+        "failed\r\na.Error\r\n     at a.Main.run(Main.java:1)"
+    );
+}
+
+#[test]
+fn test_renders_exceptions() {
+    assert!(Pattern::parse("%m%n%ex").unwrap().renders_exceptions());
+    assert!(!Pattern::parse("%m%n").unwrap().renders_exceptions());
+}
+
+#[test]
+fn test_render_strip_ansi() {
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "\x1b[32mready\x1b[0m", &[]);
+    let pattern = Pattern::parse("[%-6m]").unwrap();
+    assert_eq!(pattern.render(&evt), "[\x1b[32mready\x1b[0m]");
+    assert_eq!(pattern.with_strip_ansi().render(&evt), "[ready ]");
+}
+
+#[test]
+fn test_render_widths() {
+    let evt = LogEvent::new(LogLevel::Info, "com.example.Service", "", &[]);
+    let render = |pattern: &str| Pattern::parse(pattern).unwrap().render(&evt);
+    assert_eq!(render("[%6p]"), "[  INFO]");
+    assert_eq!(render("[%-6p]"), "[INFO  ]");
+    assert_eq!(render("[%.7logger]"), "[Service]");
+    assert_eq!(render("[%.-3logger]"), "[com]");
+    assert_eq!(render("[%logger{0}]"), "[Service]");
+    assert_eq!(render("[%d{dd/MM/yy 'at' HH:mm}]"), "[01/01/70 at 00:00]");
+}
+
+#[test]
+fn test_render_mdc_sorted() {
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    for key in ["user", "session", "request", "app", "tenant"] {
+        evt.mdc.insert(key.into(), key.to_uppercase());
+    }
+    let pattern = Pattern::parse("%X").unwrap();
+    assert_eq!(
+        pattern.render(&evt),
+        "app=APP, request=REQUEST, session=SESSION, tenant=TENANT, user=USER"
+    );
+}
+
+#[test]
+fn test_custom_words() {
+    let words = WordRegistry::new()
+        .register("app", |evt| evt.context().name().to_uppercase())
+        .register("level", |_| "ignored".into());
+    let pattern = Pattern::parse_with("[%-8app] %level %msg", &words).unwrap();
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", "started", &[]);
+    assert_eq!(pattern.render(&evt), "[DEFAULT ] INFO started");
+    assert_eq!(
+        Pattern::parse("%app").unwrap_err().kind,
+        PatternErrorKind::UnknownConversion("app".into())
+    );
+}
+
+#[test]
+fn test_validate() {
+    use PatternErrorKind::*;
+    let error = |pattern: &str| Pattern::validate(pattern).unwrap_err();
+    assert!(Pattern::validate("%d %-5level [%t] %logger{36} - %msg%n").is_ok());
+    assert_eq!(
+        error("%d %lvl"),
+        PatternError {
+            position: 4,
+            kind: UnknownConversion("lvl".into())
+        }
+    );
+    assert_eq!(
+        error("%msg %X{user"),
+        PatternError {
+            position: 7,
+            kind: UnterminatedOption
+        }
+    );
+    assert_eq!(
+        error("%msg %5.level"),
+        PatternError {
+            position: 6,
+            kind: InvalidWidth
+        }
+    );
+    assert_eq!(
+        error("%-level"),
+        PatternError {
+            position: 1,
+            kind: InvalidWidth
+        }
+    );
+    assert_eq!(
+        error("%logger{ten}"),
+        PatternError {
+            position: 1,
+            kind: InvalidOption("ten".into())
+        }
+    );
+    assert_eq!(
+        error("%d{yyyy-QQ}"),
+        PatternError {
+            position: 1,
+            kind: InvalidOption("yyyy-QQ".into())
+        }
+    );
+    assert_eq!(
+        error("100%"),
+        PatternError {
+            position: 3,
+            kind: MissingConversion
+        }
+    );
+}
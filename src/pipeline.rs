@@ -0,0 +1,95 @@
+use crate::{Error, Filter, LogEvent};
+
+/// A step in processing a stream of events
+///
+/// Each transform can modify an event or drop it by returning `None`.
+pub trait Transform {
+    fn apply(&mut self, evt: LogEvent) -> Option<LogEvent>;
+}
+
+impl<F: FnMut(LogEvent) -> Option<LogEvent>> Transform for F {
+    fn apply(&mut self, evt: LogEvent) -> Option<LogEvent> {
+        self(evt)
+    }
+}
+
+impl Transform for Filter {
+    fn apply(&mut self, evt: LogEvent) -> Option<LogEvent> {
+        self.matches(&evt).then_some(evt)
+    }
+}
+
+/// A chain of transforms applied in the order they were added
+///
+/// Once a transform drops an event, later transforms don't see it.
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<Box<dyn Transform + Send>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a transform to the end of the pipeline
+    pub fn then(mut self, step: impl Transform + Send + 'static) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+}
+
+impl Transform for Pipeline {
+    fn apply(&mut self, evt: LogEvent) -> Option<LogEvent> {
+        self.steps
+            .iter_mut()
+            .try_fold(evt, |evt, step| step.apply(evt))
+    }
+}
+
+/// Iterator over the events that pass through a pipeline
+///
+/// See [`EventStreamExt::through`](crate::EventStreamExt::through).
+pub struct Through<I> {
+    pub(crate) events: I,
+    pub(crate) pipeline: Pipeline,
+}
+
+impl<I: Iterator<Item = Result<LogEvent, Error>>> Iterator for Through<I> {
+    type Item = Result<LogEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next()? {
+                Ok(evt) => match self.pipeline.apply(evt) {
+                    Some(evt) => return Some(Ok(evt)),
+                    None => continue,
+                },
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_pipeline() {
+    use crate::{EventStreamExt, LogEventStream, LogLevel};
+
+    let redact = |mut evt: LogEvent| {
+        evt.mdc.insert("user".into(), "***".into());
+        Some(evt)
+    };
+    let pipeline = Pipeline::new()
+        .then(Filter::new().min_level(LogLevel::Warn))
+        .then(redact);
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let events = LogEventStream::new(data)
+        .unwrap()
+        .through(pipeline)
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].level, LogLevel::Warn);
+    assert_eq!(events[1].level, LogLevel::Error);
+    assert!(events.iter().all(|evt| evt.mdc["user"] == "***"));
+}
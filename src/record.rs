@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{LogEvent, LogLevel, ThrowableRenderer};
+
+/// A self-contained copy of a log event that can be serialized with serde
+///
+/// The message is formatted and any throwable is rendered as its full trace,
+/// including causes and suppressed throwables, so a record can be read back
+/// without the original event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// Milliseconds since the unix epoch
+    pub time_stamp: i64,
+    pub level: LogLevel,
+    pub logger: String,
+    pub thread: String,
+    pub context: String,
+    pub message: String,
+    /// Every argument of the event, even those the message had no anchor for
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    pub mdc: BTreeMap<String, String>,
+    pub key_value_pairs: Vec<(String, String)>,
+    pub marker: Option<String>,
+    pub throwable: Option<String>,
+}
+
+impl From<&LogEvent> for Record {
+    fn from(evt: &LogEvent) -> Self {
+        Self {
+            time_stamp: evt.time_stamp,
+            level: evt.level,
+            logger: evt.logger_name.0.clone(),
+            thread: evt.thread_name.clone(),
+            context: evt.context().name().into(),
+            message: evt.message().into_owned(),
+            arguments: evt.arguments.clone(),
+            mdc: evt
+                .mdc
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            key_value_pairs: evt.key_value_pairs.clone(),
+            marker: evt.marker.as_ref().map(|m| m.name.clone()),
+            throwable: evt
+                .throwable
+                .as_ref()
+                .map(|t| ThrowableRenderer::new().render(t)),
+        }
+    }
+}
+
+impl Record {
+    /// Read a record written by [`to_json_line`]
+    pub fn from_json_line(line: &str) -> Result<Record, serde_json::Error> {
+        serde_json::from_str(line.trim_end())
+    }
+}
+
+/// Serialize an event as a [`Record`] encoded as a single line of JSON, without
+/// a trailing new line
+pub fn to_json_line(evt: &LogEvent) -> String {
+    serde_json::to_string(&Record::from(evt)).expect("records are always serializable")
+}
+
+/// Options for the extra content [`to_json_line_with`] can add to a record
+///
+/// The default options write the same lines as [`to_json_line`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonLineOptions {
+    /// If the message is itself JSON (see [`LogEvent::message_is_json`]),
+    /// include it as a nested value instead of a string. Lines with an inlined
+    /// message can't be read back with [`Record::from_json_line`].
+    pub inline_json_message: bool,
+    /// Include the location the event was logged from (see
+    /// [`LogEvent::caller`]) as `log.origin`, following the Elastic Common
+    /// Schema, eg `"log":{"origin":{"file":{"name":"Main.java","line":3},
+    /// "function":"run","class":"a.Main"}}`. It is left out if the event has
+    /// no caller data.
+    pub include_origin: bool,
+}
+
+/// As [`to_json_line`] but with extra content chosen by the options
+pub fn to_json_line_with(evt: &LogEvent, options: &JsonLineOptions) -> String {
+    let inline_message = options.inline_json_message && evt.message_is_json();
+    let origin = evt.caller().filter(|_| options.include_origin);
+    if !inline_message && origin.is_none() {
+        return to_json_line(evt);
+    }
+    let mut json =
+        serde_json::to_value(Record::from(evt)).expect("records are always serializable");
+    if inline_message {
+        if let Ok(message) = serde_json::from_str(&evt.message()) {
+            json["message"] = message;
+        }
+    }
+    if let Some(frame) = origin {
+        json["log"] = serde_json::json!({
+            "origin": {
+                "file": {
+                    "name": frame.file_name(),
+                    "line": frame.line(),
+                },
+                "function": frame.method_name(),
+                "class": frame.class_name(),
+            }
+        });
+    }
+    json.to_string()
+}
+
+/// Serialize an event as a MessagePack encoded [`Record`]
+pub fn to_msgpack(evt: &LogEvent) -> Vec<u8> {
+    // Records only contain strings, numbers and maps with string keys so
+    // encoding them can't fail
+    rmp_serde::to_vec_named(&Record::from(evt)).expect("records are always serializable")
+}
+
+/// Read a [`Record`] previously written by [`to_msgpack`]
+pub fn record_from_msgpack(data: &[u8]) -> Result<Record, rmp_serde::decode::Error> {
+    rmp_serde::from_slice(data)
+}
+
+#[test]
+fn test_msgpack_round_trip() {
+    let mut evt = LogEvent::new(LogLevel::Warn, "a.Main", "Ran {} of {}", &["3", "4"]);
+    evt.time_stamp = 1_689_000_001_500;
+    evt.mdc.insert("user".into(), "abc".into());
+    evt.throwable = Some(
+        crate::Throwable::new("java.io.IOException", &[("a.Main", "run")])
+            .caused_by(crate::Throwable::new("a.DiskFull", &[])),
+    );
+
+    let data = to_msgpack(&evt);
+    let record = record_from_msgpack(&data).unwrap();
+    assert_eq!(record, Record::from(&evt));
+    assert_eq!(record.message, "Ran 3 of 4");
+    assert_eq!(record.level, LogLevel::Warn);
+    assert_eq!(record.mdc["user"], "abc");
+    assert!(record.key_value_pairs.is_empty());
+    assert_eq!(
+        record.throwable.as_deref(),
+        Some("java.io.IOException\n     at a.Main.run(Main.java:1)\nCaused by: a.DiskFull")
+    );
+
+    assert_eq!(record.arguments, ["3", "4"]);
+
+    assert!(record_from_msgpack(&data[..data.len() / 2]).is_err());
+}
+
+#[test]
+fn test_record_key_value_pairs() {
+    let evt = crate::read_event(include_bytes!("../tests/fixtures/key_value_pairs.ser"));
+    let record = record_from_msgpack(&to_msgpack(&evt)).unwrap();
+    assert_eq!(
+        record.key_value_pairs,
+        [
+            ("orderId".to_string(), "A-17".to_string()),
+            ("items".to_string(), "3".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_unused_arguments_kept() {
+    let evt = LogEvent::new(
+        LogLevel::Info,
+        "a.Main",
+        "Loaded {}",
+        &["config", "3", "ms"],
+    );
+    let json = serde_json::to_value(Record::from(&evt)).unwrap();
+    assert_eq!(json["message"], "Loaded config");
+    assert_eq!(json["arguments"], serde_json::json!(["config", "3", "ms"]));
+}
+
+#[test]
+fn test_json_line_round_trip() {
+    let mut evt = LogEvent::new(LogLevel::Unknown, "a.Main", "Line {}\nand more", &["one"]);
+    evt.time_stamp = 1_689_000_001_501;
+    evt.mdc.insert("user".into(), "abc".into());
+
+    let line = to_json_line(&evt);
+    assert!(!line.contains('\n'));
+    assert!(line.contains(r#""level":"UNKNOWN""#));
+    let record = Record::from_json_line(&format!("{line}\n")).unwrap();
+    assert_eq!(record, Record::from(&evt));
+    assert_eq!(record.time_stamp, 1_689_000_001_501);
+    assert_eq!(record.level, LogLevel::Unknown);
+    assert_eq!(record.message, "Line one\nand more");
+
+    assert!(Record::from_json_line(&line[..line.len() - 1]).is_err());
+}
+
+#[test]
+fn test_json_line_inline_message() {
+    let options = JsonLineOptions {
+        inline_json_message: true,
+        ..Default::default()
+    };
+    let evt = LogEvent::new(LogLevel::Info, "a.Main", r#"{"order": "{}"}"#, &["A-17"]);
+    let line = to_json_line_with(&evt, &options);
+    assert!(line.contains(r#""message":{"order":"A-17"}"#));
+    assert_eq!(
+        to_json_line_with(&evt, &JsonLineOptions::default()),
+        to_json_line(&evt)
+    );
+    let plain = LogEvent::new(LogLevel::Info, "a.Main", "{not json}", &[]);
+    assert_eq!(to_json_line_with(&plain, &options), to_json_line(&plain));
+}
+
+#[test]
+fn test_json_line_origin() {
+    let options = JsonLineOptions {
+        include_origin: true,
+        ..Default::default()
+    };
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "started", &[]);
+    assert_eq!(to_json_line_with(&evt, &options), to_json_line(&evt));
+
+    evt.stacktrace = Some(vec![
+        crate::StackFrame::new("a.Main", "run", 3),
+        crate::StackFrame::new("a.Main", "main", 9),
+    ]);
+    let line = to_json_line_with(&evt, &options);
+    let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(json["log"]["origin"]["file"]["line"], 3);
+    assert_eq!(json["log"]["origin"]["file"]["name"], "Main.java");
+    assert_eq!(json["log"]["origin"]["function"], "run");
+    assert_eq!(json["log"]["origin"]["class"], "a.Main");
+    // The origin is extra to the record so the line can still be read back
+    assert_eq!(Record::from_json_line(&line).unwrap(), Record::from(&evt));
+
+    assert!(to_json_line_with(&evt, &JsonLineOptions::default())
+        .parse::<serde_json::Value>()
+        .unwrap()
+        .get("log")
+        .is_none());
+}
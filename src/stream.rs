@@ -0,0 +1,610 @@
+use jaded::{Content, ConversionError, FromJava, JavaError, Parser, StreamError, Value};
+use std::{
+    io::{self, Read, StdinLock},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{Error, LogEvent, LogLevel};
+
+/// Iterator over the events in a serialized stream, eg a file or the socket of
+/// a ServerSocketAppender
+///
+/// The stream ends at the end of the underlying source or after the first
+/// error reading from it, as the parser can't recover its position after one
+/// (see [`LogEventStream::with_resync`]).
+/// Objects that can be read but are not valid events are yielded as errors
+/// without ending the stream.
+pub struct LogEventStream<R: Read> {
+    parser: Parser<Counted<R>>,
+    source: Counted<R>,
+    events: u64,
+    min_level: Option<LogLevel>,
+    resync: bool,
+    progress: Option<Box<dyn FnMut(u64) + Send>>,
+    done: bool,
+}
+
+impl<R: Read> LogEventStream<R> {
+    pub fn new(source: R) -> Result<Self, Error> {
+        let source = Counted(Arc::new(Mutex::new(Source {
+            inner: source,
+            bytes: 0,
+            recent: vec![],
+            pending: vec![],
+        })));
+        Ok(Self {
+            parser: Parser::new(source.clone())?,
+            source,
+            events: 0,
+            min_level: None,
+            resync: false,
+            progress: None,
+            done: false,
+        })
+    }
+
+    /// Create a stream over a source previously read up to the given checkpoint
+    ///
+    /// Objects in a serialized stream refer back to class descriptions written
+    /// with earlier objects so reading can't start part way through. Instead,
+    /// the events already seen are read again and discarded (without being
+    /// converted into `LogEvent`s). The source must therefore replay the stream
+    /// from its start, which rules out sockets and other non-seekable sources
+    /// that can't be reopened. Resuming is always linear: seeking to the
+    /// checkpoint's byte offset would leave the parser without the classes
+    /// the remaining events refer to.
+    ///
+    /// The offset is instead used to check that the events read again are the
+    /// ones the checkpoint was taken after. If they end at a different offset
+    /// the source is not the same stream and an `InvalidData` error is
+    /// returned. A checkpoint taken before any events were read (including the
+    /// default) resumes from the start of any stream.
+    pub fn resume_from(source: R, checkpoint: &Checkpoint) -> Result<Self, Error> {
+        let mut stream = Self::new(source)?;
+        while stream.events < checkpoint.events {
+            stream.parser.read().map_err(JavaError::from)?;
+            stream.events += 1;
+        }
+        if checkpoint.events > 0 && stream.bytes_consumed() != checkpoint.offset {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} events end at offset {}, not {}",
+                    checkpoint.events,
+                    stream.bytes_consumed(),
+                    checkpoint.offset
+                ),
+            )
+            .into());
+        }
+        Ok(stream)
+    }
+
+    /// Skip events below the given level
+    ///
+    /// Levels are compared using the ordering of [`LogLevel`] so events with
+    /// an unknown level are always kept.
+    ///
+    /// The level of each event is checked before it is converted into a
+    /// `LogEvent` so skipped events don't pay for copying their messages, MDC
+    /// etc. The parser can't skip the unread part of an object though, so every
+    /// event is still read from the source in full.
+    pub fn with_min_level(mut self, level: LogLevel) -> Self {
+        self.min_level = Some(level);
+        self
+    }
+
+    /// Look for a new serialization stream after an error instead of ending
+    ///
+    /// This allows multiple serialized dumps that have been concatenated into
+    /// a single file to be read as one stream. Anything between the error and
+    /// the next stream header is skipped. If anything other than the header was
+    /// skipped, the original error is still returned.
+    pub fn with_resync(mut self) -> Self {
+        self.resync = true;
+        self
+    }
+
+    /// Call the given function with the number of bytes consumed after each
+    /// event is read, including events skipped by [`Self::with_min_level`]
+    ///
+    /// Combined with the length of a file, this can be used to show progress
+    /// through it. Sockets and other sources that can't seek have no total
+    /// length so only the count is available.
+    pub fn with_progress(mut self, progress: impl FnMut(u64) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// End the stream after at most `n` events
+    ///
+    /// Nothing more is read from the source after the last event so this
+    /// doesn't wait for a server that sends a known number of events to close
+    /// its connection. Objects that aren't events count towards the limit.
+    pub fn take_events(self, n: usize) -> std::iter::Take<Self> {
+        self.take(n)
+    }
+
+    /// Read the next `n` events, or up to the end of the stream if it has
+    /// fewer, stopping at the first error
+    pub fn read_n(&mut self, n: usize) -> Result<Vec<LogEvent>, Error> {
+        self.by_ref().take(n).collect()
+    }
+
+    /// The number of bytes read from the source so far
+    pub fn bytes_consumed(&self) -> u64 {
+        self.source.lock().bytes
+    }
+
+    /// Scan the source for the header of a new serialization stream and create
+    /// a new parser starting from it. Returns false if there was no header
+    /// before the end of the source.
+    fn restart(&mut self) -> bool {
+        let mut window = self.source.lock().recent.clone();
+        while !window.ends_with(&HEADER) {
+            let mut next = [0];
+            match self.source.read_exact(&mut next) {
+                Ok(_) => window.push(next[0]),
+                Err(_) => return false,
+            }
+        }
+        self.source.lock().pending = HEADER.to_vec();
+        match Parser::new(self.source.clone()) {
+            Ok(parser) => {
+                self.parser = parser;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Whether an event read from the stream should be skipped
+    fn below_threshold(&self, content: &Content) -> bool {
+        match (self.min_level, content) {
+            (Some(min), Content::Object(Value::Object(data)))
+                if data.class_name() == EVENT_CLASS =>
+            {
+                data.get_annotation(0)
+                    .and_then(|mut anno| crate::converters::read_level(data, &mut anno).ok())
+                    .is_some_and(|level| LogLevel::from(level) < min)
+            }
+            _ => false,
+        }
+    }
+
+    /// The position of this stream after the events read so far
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            events: self.events,
+            offset: self.bytes_consumed(),
+        }
+    }
+}
+
+impl LogEventStream<StdinLock<'static>> {
+    /// Read events piped into this process, eg `nc logserver 6750 | logtool`
+    ///
+    /// Stdin is locked for as long as the stream exists. The lock is already
+    /// buffered so it is not wrapped in another `BufReader`. Creating the
+    /// stream blocks until the stream header has been read.
+    pub fn from_stdin() -> Result<Self, Error> {
+        Self::new(io::stdin().lock())
+    }
+}
+
+impl<R: Read> LogEventStream<R> {
+    /// Read every object in the stream instead of only events
+    ///
+    /// Some feeds carry other objects between events, eg a handshake or
+    /// snapshots of the logger context. Reading these as events fails, so this
+    /// yields them separately to be handled or skipped by the caller.
+    pub fn into_items(self) -> StreamItems<R> {
+        StreamItems(self)
+    }
+
+    /// Read the next object from the stream, skipping events below the
+    /// minimum level and restarting after errors if resync is enabled
+    fn read_content(&mut self) -> Option<Result<Content, Error>> {
+        while !self.done {
+            let start = self.source.lock().bytes;
+            match self.parser.read() {
+                Ok(content) => {
+                    self.events += 1;
+                    if let Some(progress) = &mut self.progress {
+                        progress(self.source.lock().bytes);
+                    }
+                    if self.below_threshold(&content) {
+                        continue;
+                    }
+                    return Some(Ok(content));
+                }
+                // Nothing left to read between events is the normal end of the
+                // stream rather than a truncated event.
+                Err(StreamError::EndOfStream(_)) if self.source.lock().bytes == start => {
+                    self.done = true;
+                }
+                Err(e) => {
+                    self.done = !(self.resync && self.restart());
+                    // If the only thing read was the new header, the error
+                    // was the boundary between two streams
+                    if self.done || self.source.lock().bytes - start != HEADER.len() as u64 {
+                        return Some(Err(JavaError::from(e).into()));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: Read> Iterator for LogEventStream<R> {
+    type Item = Result<LogEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.read_content()?.and_then(|content| {
+            match content {
+                Content::Object(value) => LogEvent::from_value(&value),
+                Content::Block(data) => Err(ConversionError::UnexpectedBlockData(data)),
+            }
+            .map_err(|e| JavaError::from(e).into())
+        }))
+    }
+}
+
+/// An object read from a stream that may contain things other than events
+// Events are by far the most common item so are not boxed
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum StreamItem {
+    Event(LogEvent),
+    /// Anything that isn't an event. The class is `None` for nulls, arrays and
+    /// block data.
+    Other {
+        class: Option<String>,
+        content: Content,
+    },
+}
+
+/// Iterator over every object in a stream (see [`LogEventStream::into_items`])
+pub struct StreamItems<R: Read>(LogEventStream<R>);
+
+impl<R: Read> Iterator for StreamItems<R> {
+    type Item = Result<StreamItem, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.0.read_content()?.and_then(|content| {
+            let class = match &content {
+                Content::Object(Value::Object(data)) => Some(data.class_name()),
+                Content::Object(Value::JavaString(_)) => Some("java.lang.String"),
+                Content::Object(Value::Enum(class, _)) => Some(class.as_str()),
+                Content::Object(Value::Class(_)) => Some("java.lang.Class"),
+                _ => None,
+            };
+            match (class, &content) {
+                (Some(EVENT_CLASS), Content::Object(value)) => LogEvent::from_value(value)
+                    .map(StreamItem::Event)
+                    .map_err(|e| JavaError::from(e).into()),
+                _ => Ok(StreamItem::Other {
+                    class: class.map(String::from),
+                    content,
+                }),
+            }
+        }))
+    }
+}
+
+/// Anything that events can be read from, eg a [`LogEventStream`] or
+/// synthetic events in tests
+///
+/// Every iterator of event results is a source. Other types can implement
+/// `next_event` and use [`EventSource::events`] to get an iterator that works
+/// with the [adapters](crate::EventStreamExt).
+pub trait EventSource {
+    /// Read the next event, or `None` if there are no more
+    fn next_event(&mut self) -> Option<Result<LogEvent, Error>>;
+
+    /// Iterate over the events from this source
+    fn events(self) -> Events<Self>
+    where
+        Self: Sized,
+    {
+        Events(self)
+    }
+}
+
+impl<I: Iterator<Item = Result<LogEvent, Error>>> EventSource for I {
+    fn next_event(&mut self) -> Option<Result<LogEvent, Error>> {
+        self.next()
+    }
+}
+
+/// Iterator over the events from an [`EventSource`]
+pub struct Events<S>(S);
+
+impl<S: EventSource> Iterator for Events<S> {
+    type Item = Result<LogEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_event()
+    }
+}
+
+/// The position in a stream after a number of events have been read
+///
+/// The byte offset is the length of the stream up to that point and can be
+/// compared with the size of a file to see whether any events have been written
+/// since the checkpoint was taken. It is not a position that reading can start
+/// from (see [`LogEventStream::resume_from`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Checkpoint {
+    pub events: u64,
+    pub offset: u64,
+}
+
+/// The class serialized by logback for each event
+const EVENT_CLASS: &str = "ch.qos.logback.classic.spi.LoggingEventVO";
+
+/// The magic number and version at the start of every serialization stream
+const HEADER: [u8; 4] = [0xAC, 0xED, 0x00, 0x05];
+
+/// Handle to a source shared between the stream and its parser so that a new
+/// parser can be created if the stream needs to restart
+struct Counted<R>(Arc<Mutex<Source<R>>>);
+
+struct Source<R> {
+    inner: R,
+    /// The number of bytes read from the source
+    bytes: u64,
+    /// The last few bytes read, in case they were the start of a new stream
+    recent: Vec<u8>,
+    /// Bytes that have been read from the source already but need to be read
+    /// again by a new parser
+    pending: Vec<u8>,
+}
+
+impl<R> Counted<R> {
+    fn lock(&self) -> MutexGuard<'_, Source<R>> {
+        self.0.lock().unwrap()
+    }
+}
+
+impl<R> Clone for Counted<R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut source = self.lock();
+        if !source.pending.is_empty() {
+            let len = buf.len().min(source.pending.len());
+            buf[..len].copy_from_slice(&source.pending[..len]);
+            source.pending.drain(..len);
+            return Ok(len);
+        }
+        let read = source.inner.read(buf)?;
+        source.bytes += read as u64;
+        let keep = HEADER.len().saturating_sub(read);
+        let drop = source.recent.len().saturating_sub(keep);
+        source.recent.drain(..drop);
+        source
+            .recent
+            .extend_from_slice(&buf[read.saturating_sub(HEADER.len())..read]);
+        Ok(read)
+    }
+}
+
+#[test]
+fn test_resume_from_checkpoint() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let mut stream = LogEventStream::new(data).unwrap();
+    for _ in 0..3 {
+        stream.next().unwrap().unwrap();
+    }
+    let checkpoint = stream.checkpoint();
+    assert_eq!(checkpoint.events, 3);
+    assert!(checkpoint.offset > 4 && checkpoint.offset < data.len() as u64);
+
+    let resumed = LogEventStream::resume_from(data, &checkpoint)
+        .unwrap()
+        .map(|evt| evt.unwrap().message().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(resumed, ["event 4", "event 5"]);
+}
+
+#[test]
+fn test_resume_from_mismatched_checkpoint() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let mut stream = LogEventStream::new(data).unwrap();
+    stream.next().unwrap().unwrap();
+    let checkpoint = Checkpoint {
+        offset: stream.checkpoint().offset + 1,
+        ..stream.checkpoint()
+    };
+    match LogEventStream::resume_from(data, &checkpoint) {
+        Err(Error::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::InvalidData),
+        _ => panic!("expected the checkpoint to be rejected"),
+    }
+
+    let resumed = LogEventStream::resume_from(data, &Checkpoint::default()).unwrap();
+    assert_eq!(resumed.count(), 5);
+}
+
+#[test]
+fn test_checkpoint_default() {
+    let checkpoint = Checkpoint {
+        events: 3,
+        ..Default::default()
+    };
+    assert_eq!(checkpoint.offset, 0);
+    assert_eq!(Checkpoint::default().events, 0);
+}
+
+#[test]
+fn test_read_n() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let mut stream = LogEventStream::new(data).unwrap();
+    let first = stream.read_n(3).unwrap();
+    assert_eq!(first.len(), 3);
+    assert_eq!(first[2].message(), "event 3");
+    assert_eq!(stream.checkpoint().events, 3);
+    assert_eq!(stream.read_n(3).unwrap().len(), 2);
+
+    let taken = LogEventStream::new(data).unwrap().take_events(3).count();
+    assert_eq!(taken, 3);
+}
+
+#[test]
+fn test_min_level() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let mut stream = LogEventStream::new(data)
+        .unwrap()
+        .with_min_level(LogLevel::Info);
+    let levels = stream
+        .by_ref()
+        .map(|evt| evt.unwrap().level)
+        .collect::<Vec<_>>();
+    assert_eq!(levels, [LogLevel::Info, LogLevel::Warn, LogLevel::Error]);
+    assert_eq!(stream.checkpoint().events, 5);
+}
+
+#[test]
+fn test_min_level_keeps_unknown() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/unknown_level.ser");
+    let levels = LogEventStream::new(data)
+        .unwrap()
+        .with_min_level(LogLevel::Warn)
+        .map(|evt| evt.unwrap().level)
+        .collect::<Vec<_>>();
+    assert_eq!(levels, [LogLevel::Unknown, LogLevel::Error]);
+}
+
+#[test]
+fn test_buffered_source() {
+    // Stdin is read through a buffered lock so check that buffering the
+    // source doesn't change what is read or the bytes counted
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let mut stream = LogEventStream::new(io::BufReader::with_capacity(7, data)).unwrap();
+    assert_eq!(stream.by_ref().filter(Result::is_ok).count(), 5);
+    assert_eq!(stream.bytes_consumed(), data.len() as u64);
+}
+
+#[test]
+fn test_progress() {
+    use std::fs::File;
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/five_events.ser"
+    );
+    let len = std::fs::metadata(path).unwrap().len();
+    let seen = Arc::new(Mutex::new(vec![]));
+    let progress = seen.clone();
+    let mut stream = LogEventStream::new(File::open(path).unwrap())
+        .unwrap()
+        .with_progress(move |bytes| progress.lock().unwrap().push(bytes));
+    assert!(stream.bytes_consumed() > 0);
+    assert_eq!(stream.by_ref().count(), 5);
+    assert_eq!(stream.bytes_consumed(), len);
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 5);
+    assert!(seen.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(seen[4], len);
+}
+
+#[test]
+fn test_resync_concatenated_streams() {
+    let mut data = include_bytes!("../tests/fixtures/null_context.ser").to_vec();
+    data.extend_from_slice(include_bytes!("../tests/fixtures/exception.ser"));
+
+    let mut stream = LogEventStream::new(&data[..]).unwrap();
+    assert!(stream.next().unwrap().is_ok());
+    assert!(stream.next().unwrap().is_err());
+    assert!(stream.next().is_none());
+
+    let messages = LogEventStream::new(&data[..])
+        .unwrap()
+        .with_resync()
+        .map(|evt| evt.unwrap().message().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["No context", "Failed to start service"]);
+}
+
+#[test]
+fn test_resync_reports_skipped_data() {
+    let mut data = include_bytes!("../tests/fixtures/null_context.ser").to_vec();
+    data.extend_from_slice(b"garbage");
+    data.extend_from_slice(include_bytes!("../tests/fixtures/exception.ser"));
+
+    let results = LogEventStream::new(&data[..])
+        .unwrap()
+        .with_resync()
+        .map(|evt| evt.is_ok())
+        .collect::<Vec<_>>();
+    assert_eq!(results, [true, false, true]);
+}
+
+#[test]
+fn test_mixed_stream() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/mixed.ser");
+    let items = LogEventStream::new(data)
+        .unwrap()
+        .into_items()
+        .map(|item| match item.unwrap() {
+            StreamItem::Event(evt) => evt.message().into_owned(),
+            StreamItem::Other { class, .. } => format!("<{}>", class.unwrap()),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        items,
+        [
+            "<java.lang.String>",
+            "first",
+            "<ch.qos.logback.classic.spi.LoggerContextVO>",
+            "second"
+        ]
+    );
+
+    // Non-events are still errors when only reading events
+    let events = LogEventStream::new(data)
+        .unwrap()
+        .map(|evt| evt.is_ok())
+        .collect::<Vec<_>>();
+    assert_eq!(events, [false, true, false, true]);
+
+    // and the level threshold only applies to events
+    let levels = LogEventStream::new(data)
+        .unwrap()
+        .with_min_level(LogLevel::Warn)
+        .into_items()
+        .map(|item| matches!(item.unwrap(), StreamItem::Event(_)))
+        .collect::<Vec<_>>();
+    assert_eq!(levels, [false, false, true]);
+}
+
+#[test]
+fn test_custom_event_source() {
+    use crate::EventStreamExt;
+    struct Synthetic(Vec<LogEvent>);
+    impl EventSource for Synthetic {
+        fn next_event(&mut self) -> Option<Result<LogEvent, Error>> {
+            self.0.pop().map(Ok)
+        }
+    }
+
+    let source = Synthetic(vec![
+        LogEvent::new(LogLevel::Info, "a.Main", "second", &[]),
+        LogEvent::new(LogLevel::Info, "a.Main", "first", &[]),
+    ]);
+    let messages = source
+        .events()
+        .metered()
+        .map(|evt| evt.unwrap().message().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["first", "second"]);
+
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let mut stream = LogEventStream::new(data).unwrap();
+    assert_eq!(stream.next_event().unwrap().unwrap().message(), "event 1");
+}
@@ -0,0 +1,139 @@
+use std::fmt::Write;
+
+use crate::LogEvent;
+
+/// Render an event as an RFC 5424 syslog line using the user-level facility
+///
+/// See [`to_syslog_rfc5424_with`].
+pub fn to_syslog_rfc5424(evt: &LogEvent, hostname: &str, appname: &str) -> String {
+    to_syslog_rfc5424_with(evt, hostname, appname, 1)
+}
+
+/// Render an event as an RFC 5424 syslog line with the given facility, eg
+/// `<12>1 2023-07-10T14:40:01.500Z ws001 app - - [mdc user="abc"] Disk full`
+///
+/// The priority combines the facility with the level's
+/// [syslog severity](crate::LogLevel::syslog_severity). Facilities above 23
+/// (`local7`), the highest RFC 5424 defines, are clamped to 23. The MDC is
+/// included as structured data with the ID `mdc`, sorted by key. Keys are
+/// reduced to the characters allowed in parameter names and entries whose key
+/// is left empty, or the same as an earlier key's, are skipped. The process and
+/// message IDs are always nil (`-`), as is the structured data if there are no
+/// MDC entries to include.
+pub fn to_syslog_rfc5424_with(
+    evt: &LogEvent,
+    hostname: &str,
+    appname: &str,
+    facility: u8,
+) -> String {
+    let pri = u16::from(facility.min(23)) * 8 + u16::from(evt.level.syslog_severity());
+    let time = evt.format_time("yyyy-MM-dd'T'HH:mm:ss.SSS'Z'").unwrap();
+    let mut line = format!(
+        "<{pri}>1 {time} {} {} - - ",
+        header_field(hostname, 255),
+        header_field(appname, 48)
+    );
+    let mut params = Vec::<(String, &str)>::new();
+    for (key, value) in evt.mdc_sorted() {
+        let name = param_name(key);
+        if !name.is_empty() && params.iter().all(|(n, _)| *n != name) {
+            params.push((name, value));
+        }
+    }
+    if params.is_empty() {
+        line.push('-');
+    } else {
+        line.push_str("[mdc");
+        for (name, value) in params {
+            _ = write!(line, " {name}=\"{}\"", param_value(value));
+        }
+        line.push(']');
+    }
+    let message = evt.message();
+    if !message.is_empty() {
+        line.push(' ');
+        line.push_str(&message);
+    }
+    line
+}
+
+/// A header field limited to printable ASCII without spaces, or `-` if there
+/// is nothing left
+fn header_field(value: &str, max: usize) -> String {
+    let field = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max)
+        .collect::<String>();
+    if field.is_empty() {
+        "-".into()
+    } else {
+        field
+    }
+}
+
+/// A structured data parameter name can't contain `=`, ` `, `]` or `"` and is
+/// at most 32 characters
+fn param_name(key: &str) -> String {
+    key.chars()
+        .filter(|c| c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"'))
+        .take(32)
+        .collect()
+}
+
+/// `"`, `\` and `]` must be escaped in structured data parameter values
+fn param_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[test]
+fn test_syslog_rfc5424() {
+    use crate::LogLevel;
+    let mut evt = LogEvent::new(LogLevel::Warn, "a.Main", "Disk {} full", &["/tmp"]);
+    evt.time_stamp = 1_689_000_001_500;
+    assert_eq!(
+        to_syslog_rfc5424(&evt, "ws001", "my app"),
+        "<12>1 2023-07-10T14:40:01.500Z ws001 myapp - - - Disk /tmp full"
+    );
+
+    evt.level = LogLevel::Error;
+    evt.mdc.insert("user".into(), "abc".into());
+    evt.mdc.insert("query".into(), r#"a="[1]" \ b"#.into());
+    assert_eq!(
+        to_syslog_rfc5424_with(&evt, "", "app", 16),
+        r#"<131>1 2023-07-10T14:40:01.500Z - app - - [mdc query="a=\"[1\]\" \\ b" user="abc"] Disk /tmp full"#
+    );
+}
+
+#[test]
+fn test_syslog_param_names() {
+    use crate::LogLevel;
+    let mut evt = LogEvent::new(LogLevel::Info, "a.Main", "", &[]);
+    evt.mdc.insert("===".into(), "empty".into());
+    assert_eq!(
+        to_syslog_rfc5424(&evt, "ws001", "app"),
+        "<14>1 1970-01-01T00:00:00.000Z ws001 app - - -"
+    );
+
+    evt.mdc.insert("a b".into(), "first".into());
+    evt.mdc.insert("ab".into(), "second".into());
+    assert_eq!(
+        to_syslog_rfc5424(&evt, "ws001", "app"),
+        r#"<14>1 1970-01-01T00:00:00.000Z ws001 app - - [mdc ab="first"]"#
+    );
+}
+
+#[test]
+fn test_syslog_facility_clamped() {
+    use crate::LogLevel;
+    let evt = LogEvent::new(LogLevel::Error, "a.Main", "", &[]);
+    assert!(to_syslog_rfc5424_with(&evt, "ws001", "app", 23).starts_with("<187>"));
+    assert!(to_syslog_rfc5424_with(&evt, "ws001", "app", 200).starts_with("<187>"));
+}
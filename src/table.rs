@@ -0,0 +1,246 @@
+use std::fmt::Write;
+
+use crate::{LineEnding, LogEvent};
+
+/// Renders events as rows of aligned columns: time, level, logger and message
+///
+/// Logger names are abbreviated (see `%logger{n}` in [`Pattern`](crate::Pattern))
+/// to at most the maximum logger width where possible. When rendering a batch,
+/// columns are only as wide as the widest value in the batch. Rows rendered
+/// one at a time use the maximum width so that rows from a stream line up.
+#[derive(Debug, Clone)]
+pub struct TableRenderer {
+    max_logger_width: usize,
+    line_ending: LineEnding,
+    wrap_width: Option<usize>,
+}
+
+impl Default for TableRenderer {
+    fn default() -> Self {
+        Self {
+            max_logger_width: 36,
+            line_ending: LineEnding::Lf,
+            wrap_width: None,
+        }
+    }
+}
+
+impl TableRenderer {
+    /// The separator written between columns
+    const SEPARATOR: &'static str = " | ";
+    /// The width of the level column when rendering rows one at a time
+    const LEVEL_WIDTH: usize = 5;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The width logger names are abbreviated to, 36 by default
+    pub fn max_logger_width(mut self, width: usize) -> Self {
+        self.max_logger_width = width;
+        self
+    }
+    /// End rows rendered in a batch with `\r\n` instead of `\n`
+    pub fn line_ending(mut self, ending: LineEnding) -> Self {
+        self.line_ending = ending;
+        self
+    }
+
+    /// Wrap messages at word boundaries so that rows are at most `width`
+    /// characters wide, or don't wrap them if `None` (the default)
+    ///
+    /// Continuation lines are indented to line up with the start of the
+    /// message. Words too long for the space left are broken, and at least one
+    /// character of the message is shown on each line even if the other
+    /// columns are wider than `width`.
+    pub fn wrap_width(mut self, width: Option<usize>) -> Self {
+        self.wrap_width = width;
+        self
+    }
+
+    /// Render a single event with the maximum column widths
+    pub fn render_row(&self, evt: &LogEvent) -> String {
+        self.row(evt, Self::LEVEL_WIDTH, self.max_logger_width)
+    }
+
+    /// Render all the events, one per line, with columns sized to fit them
+    pub fn render_batch(&self, events: &[LogEvent]) -> String {
+        let widest = |width: fn(&LogEvent, usize) -> usize| {
+            events
+                .iter()
+                .map(|evt| width(evt, self.max_logger_width))
+                .max()
+                .unwrap_or(0)
+        };
+        let level = widest(|evt, _| evt.level.name().len());
+        let logger = widest(|evt, max| evt.logger_name.reduced(max).chars().count());
+        events.iter().fold(String::new(), |mut out, evt| {
+            let row = self.row(evt, level, logger);
+            _ = write!(
+                out,
+                "{}{}",
+                self.line_ending.convert(&row),
+                self.line_ending.as_str()
+            );
+            out
+        })
+    }
+
+    fn row(&self, evt: &LogEvent, level: usize, logger: usize) -> String {
+        let time = evt.format_time("yyyy-MM-dd HH:mm:ss.SSS").unwrap();
+        let sep = Self::SEPARATOR;
+        let prefix = format!(
+            "{time}{sep}{:<level$}{sep}{:<logger$}{sep}",
+            evt.level,
+            evt.logger_name.reduced(self.max_logger_width),
+        );
+        let message = evt.message();
+        match self.wrap_width {
+            None => prefix + &message,
+            Some(width) => {
+                let indent = prefix.chars().count();
+                let lines = wrap(&message, width.saturating_sub(indent).max(1));
+                let continuation = format!("\n{:indent$}", "");
+                prefix + &lines.join(&continuation)
+            }
+        }
+    }
+}
+
+/// Split text into lines of at most `width` (non-zero) characters, breaking
+/// at spaces where possible. Existing line breaks are kept.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for line in text.split('\n') {
+        let mut current = String::new();
+        let mut len = 0;
+        for word in line.split(' ').filter(|w| !w.is_empty()) {
+            let mut word = word;
+            let mut word_len = word.chars().count();
+            if len > 0 && len + 1 + word_len <= width {
+                current.push(' ');
+                current.push_str(word);
+                len += 1 + word_len;
+                continue;
+            }
+            if len > 0 {
+                lines.push(std::mem::take(&mut current));
+            }
+            while word_len > width {
+                let (cut, _) = word.char_indices().nth(width).unwrap();
+                lines.push(word[..cut].into());
+                word = &word[cut..];
+                word_len -= width;
+            }
+            current.push_str(word);
+            len = word_len;
+        }
+        lines.push(current);
+    }
+    lines
+}
+
+#[test]
+fn test_wrap() {
+    assert_eq!(wrap("the quick brown fox", 10), ["the quick", "brown fox"]);
+    assert_eq!(wrap("a  b\n\nc", 10), ["a b", "", "c"]);
+    assert_eq!(
+        wrap("see https://example.com/long/path ok", 12),
+        ["see", "https://exam", "ple.com/long", "/path ok"]
+    );
+    assert_eq!(wrap("ñññññ", 2), ["ññ", "ññ", "ñ"]);
+}
+
+#[test]
+fn test_wrap_width() {
+    let evt = LogEvent::new(
+        crate::LogLevel::Info,
+        "a.Main",
+        "Loaded {} entries from the configuration file",
+        &["12"],
+    );
+    let row = TableRenderer::new()
+        .max_logger_width(6)
+        .wrap_width(Some(60))
+        .render_row(&evt);
+    let lines = row.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "1970-01-01 00:00:00.000 | INFO  | a.Main | Loaded 12 entries",
+            "                                           from the",
+            "                                           configuration",
+            "                                           file",
+        ]
+    );
+    assert!(lines.iter().all(|line| line.chars().count() <= 60));
+    let message_column = lines[0].find("Loaded").unwrap();
+    assert!(lines[1..]
+        .iter()
+        .all(|line| line.find(|c| c != ' ') == Some(message_column)));
+}
+
+#[test]
+fn test_render_batch() {
+    let events = [
+        LogEvent::new(crate::LogLevel::Info, "a.Main", "starting", &[]),
+        LogEvent::new(
+            crate::LogLevel::Error,
+            "com.example.io.Reader",
+            "failed",
+            &[],
+        ),
+        LogEvent::new(crate::LogLevel::Warn, "com.example.Service", "slow", &[]),
+    ];
+    let table = TableRenderer::new()
+        .max_logger_width(16)
+        .render_batch(&events);
+    let rows = table.lines().collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        [
+            "1970-01-01 00:00:00.000 | INFO  | a.Main        | starting",
+            "1970-01-01 00:00:00.000 | ERROR | c.e.io.Reader | failed",
+            "1970-01-01 00:00:00.000 | WARN  | c.e.Service   | slow",
+        ]
+    );
+    // Every column starts at the same position in each row
+    let columns = |row: &str| row.match_indices(" | ").map(|(i, _)| i).collect::<Vec<_>>();
+    assert!(rows.iter().all(|row| columns(row) == columns(rows[0])));
+}
+
+#[test]
+fn test_render_batch_crlf() {
+    let events = [LogEvent::new(
+        crate::LogLevel::Info,
+        "a.Main",
+        "one\ntwo",
+        &[],
+    )];
+    let table = TableRenderer::new()
+        .line_ending(LineEnding::CrLf)
+        .render_batch(&events);
+    assert_eq!(
+        table,
+        "1970-01-01 00:00:00.000 | INFO | a.Main | one\r\ntwo\r\n"
+    );
+}
+
+#[test]
+fn test_render_row() {
+    let evt = LogEvent::new(crate::LogLevel::Info, "a.Main", "starting", &[]);
+    assert_eq!(
+        TableRenderer::new().max_logger_width(10).render_row(&evt),
+        "1970-01-01 00:00:00.000 | INFO  | a.Main     | starting"
+    );
+}
+
+#[test]
+fn test_table_renderer_default() {
+    let renderer = TableRenderer {
+        wrap_width: Some(80),
+        ..Default::default()
+    };
+    assert_eq!(renderer.max_logger_width, 36);
+    assert_eq!(renderer.line_ending, LineEnding::Lf);
+    assert_eq!(TableRenderer::default().wrap_width, None);
+}
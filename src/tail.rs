@@ -0,0 +1,177 @@
+use std::io::{self, Write};
+
+use crate::{Error, LogEvent, LogLevel, Pattern, ThrowableRenderer};
+
+/// What happened during a call to [`run_tail`]
+#[derive(Debug, Default)]
+pub struct TailSummary {
+    /// The number of events read, including those below the minimum level
+    pub read: u64,
+    /// The number of events written to the output
+    pub written: u64,
+    /// The number of `Error` events read
+    pub errors: u64,
+    /// The number of events read with a level that wasn't recognised
+    pub unknown: u64,
+    /// The error that ended the stream, if it didn't end normally
+    pub error: Option<Error>,
+}
+
+/// Whether events with unrecognised levels count as errors when deciding if
+/// a run had errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownLevels {
+    #[default]
+    Ignore,
+    AsErrors,
+}
+
+impl TailSummary {
+    /// Whether any error events were read or the stream ended with an error
+    pub fn had_errors(&self, unknown: UnknownLevels) -> bool {
+        let unknown = match unknown {
+            UnknownLevels::Ignore => 0,
+            UnknownLevels::AsErrors => self.unknown,
+        };
+        self.errors + unknown > 0 || self.error.is_some()
+    }
+    /// An exit code for a command line tool: 1 if the run [had
+    /// errors](Self::had_errors), otherwise 0
+    pub fn exit_code(&self, unknown: UnknownLevels) -> i32 {
+        i32::from(self.had_errors(unknown))
+    }
+}
+
+/// Render every event at or above `min_level` to `out` until the stream ends
+///
+/// Levels are compared using the ordering of [`LogLevel`] so events with an
+/// unknown level are always rendered.
+///
+/// As with logback's `PatternLayout`, the pattern should end with `%n` and, if
+/// it doesn't include `%ex`, any exception is written after the rendered
+/// event. Reading stops at the first error from the stream, which is returned
+/// in the summary. Errors writing to the output are returned immediately.
+pub fn run_tail<I, W>(
+    events: I,
+    min_level: LogLevel,
+    pattern: &Pattern,
+    out: &mut W,
+) -> io::Result<TailSummary>
+where
+    I: IntoIterator<Item = Result<LogEvent, Error>>,
+    W: Write,
+{
+    let mut summary = TailSummary::default();
+    for evt in events {
+        let evt = match evt {
+            Ok(evt) => evt,
+            Err(e) => {
+                summary.error = Some(e);
+                break;
+            }
+        };
+        summary.read += 1;
+        match evt.level {
+            LogLevel::Error => summary.errors += 1,
+            LogLevel::Unknown => summary.unknown += 1,
+            _ => {}
+        }
+        if evt.level < min_level {
+            continue;
+        }
+        out.write_all(pattern.render(&evt).as_bytes())?;
+        if let Some(ex) = evt
+            .throwable
+            .as_ref()
+            .filter(|_| !pattern.renders_exceptions())
+        {
+            let ending = pattern.line_ending();
+            write!(
+                out,
+                "{}{}",
+                ending.convert(&ThrowableRenderer::new().render(ex)),
+                ending.as_str()
+            )?;
+        }
+        summary.written += 1;
+    }
+    out.flush()?;
+    Ok(summary)
+}
+
+#[test]
+fn test_run_tail() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/five_events.ser");
+    let pattern = Pattern::parse("%-5level %m%n").unwrap();
+    let mut out = vec![];
+    let events = crate::LogEventStream::new(data).unwrap();
+    let summary = run_tail(events, LogLevel::Info, &pattern, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "INFO  event 3\nWARN  event 4\nERROR event 5\n"
+    );
+    assert_eq!((summary.read, summary.written), (5, 3));
+    assert!(summary.error.is_none());
+    assert_eq!((summary.errors, summary.unknown), (1, 0));
+}
+
+#[test]
+fn test_run_tail_unknown_levels() {
+    let events = [LogLevel::Info, LogLevel::Unknown, LogLevel::Unknown]
+        .map(|level| Ok(LogEvent::new(level, "a.Main", "", &[])));
+    let pattern = Pattern::parse("%m%n").unwrap();
+    let summary = run_tail(events, LogLevel::Info, &pattern, &mut io::sink()).unwrap();
+    assert_eq!((summary.errors, summary.unknown), (0, 2));
+    assert_eq!(summary.exit_code(UnknownLevels::Ignore), 0);
+    assert_eq!(summary.exit_code(UnknownLevels::AsErrors), 1);
+
+    let errors = TailSummary {
+        errors: 1,
+        ..Default::default()
+    };
+    assert_eq!(errors.exit_code(UnknownLevels::Ignore), 1);
+}
+
+#[test]
+fn test_run_tail_keeps_unknown() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/unknown_level.ser");
+    let pattern = Pattern::parse("%m%n").unwrap();
+    let mut out = vec![];
+    let events = crate::LogEventStream::new(data).unwrap();
+    let summary = run_tail(events, LogLevel::Warn, &pattern, &mut out).unwrap();
+    assert_eq!(out, b"custom\nerror\n");
+    assert_eq!(summary.unknown, 1);
+}
+
+#[test]
+fn test_run_tail_adds_exception() {
+    let data: &[u8] = include_bytes!("../tests/fixtures/exception.ser");
+    let pattern = Pattern::parse("%m%n").unwrap();
+    let mut out = vec![];
+    let events = crate::LogEventStream::new(data).unwrap();
+    run_tail(events, LogLevel::Trace, &pattern, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.starts_with(
+        "Failed to start service\n\
+         java.lang.IllegalStateException: Could not load config\n"
+    ));
+    assert!(out
+        .lines()
+        .any(|line| line == "Caused by: java.io.IOException: No space left on device"));
+    assert!(out.ends_with("at com.example.Main.main(Main.java:8)\n"));
+}
+
+#[test]
+fn test_run_tail_stops_at_error() {
+    let events = vec![
+        Ok(LogEvent::new(LogLevel::Info, "a.Main", "first", &[])),
+        Err(Error::UnknownLogLevel("x".into())),
+        Ok(LogEvent::new(LogLevel::Info, "a.Main", "second", &[])),
+    ];
+    let pattern = Pattern::parse("%m%n").unwrap();
+    let mut out = vec![];
+    let summary = run_tail(events, LogLevel::Info, &pattern, &mut out).unwrap();
+    assert_eq!(out, b"first\n");
+    assert_eq!(summary.read, 1);
+    assert!(matches!(summary.error, Some(Error::UnknownLogLevel(_))));
+}
@@ -0,0 +1,231 @@
+use std::fmt::Write;
+
+use crate::{LineEnding, Throwable};
+
+/// Options for rendering a throwable and its chain of causes
+///
+/// The default renders everything except packaging data, matching
+/// [`Throwable::format_chain`] with no depth limit.
+#[derive(Debug, Clone)]
+pub struct ThrowableRenderer {
+    max_frames: Option<usize>,
+    max_depth: usize,
+    packaging_data: bool,
+    suppressed: bool,
+    causes: bool,
+    line_ending: LineEnding,
+}
+
+impl Default for ThrowableRenderer {
+    fn default() -> Self {
+        Self {
+            max_frames: None,
+            max_depth: usize::MAX,
+            packaging_data: false,
+            suppressed: true,
+            causes: true,
+            line_ending: LineEnding::Lf,
+        }
+    }
+}
+
+impl ThrowableRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Only include the top `frames` frames of each throwable. The number of
+    /// frames left out is noted after the ones included.
+    pub fn max_frames(mut self, frames: usize) -> Self {
+        self.max_frames = Some(frames);
+        self
+    }
+    /// Replace causes and suppressed throwables nested deeper than `depth`
+    /// with a note that the chain was truncated
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+    /// Include the jar and version each frame's class was loaded from, if
+    /// logback included them, eg `[service.jar:1.2]`. Versions that logback
+    /// only guessed at are prefixed with `~`.
+    pub fn packaging_data(mut self, show: bool) -> Self {
+        self.packaging_data = show;
+        self
+    }
+    pub fn suppressed(mut self, show: bool) -> Self {
+        self.suppressed = show;
+        self
+    }
+    pub fn causes(mut self, show: bool) -> Self {
+        self.causes = show;
+        self
+    }
+    /// Separate lines with `\r\n` instead of `\n`
+    pub fn line_ending(mut self, ending: LineEnding) -> Self {
+        self.line_ending = ending;
+        self
+    }
+
+    /// Render a throwable followed by its suppressed throwables and causes
+    ///
+    /// The chain is rendered without recursion so long chains can't overflow
+    /// the stack.
+    pub fn render(&self, throwable: &Throwable) -> String {
+        let mut out = String::new();
+        // (depth, indent, label, throwable) for everything still to render
+        let mut pending = vec![(0, 0, "", throwable)];
+        while let Some((depth, indent, label, t)) = pending.pop() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            let indent_str = "\t".repeat(indent);
+            if depth > self.max_depth {
+                _ = write!(
+                    out,
+                    "{indent_str}... chain truncated at depth {}",
+                    self.max_depth
+                );
+                continue;
+            }
+            out.push_str(&indent_str);
+            out.push_str(label);
+            self.render_frames(t, &indent_str, &mut out);
+            // Suppressed throwables are nested under the one that suppressed
+            // them but causes are shown at the same level
+            if let Some(cause) = t.cause.as_deref().filter(|_| self.causes) {
+                pending.push((depth + 1, indent, "Caused by: ", cause));
+            }
+            if self.suppressed {
+                for sup in t.suppressed.iter().rev() {
+                    pending.push((depth + 1, indent + 1, "Suppressed: ", sup));
+                }
+            }
+        }
+        self.line_ending.convert_owned(out)
+    }
+
+    /// Render the class and message of a single throwable and its frames
+    fn render_frames(&self, t: &Throwable, indent: &str, out: &mut String) {
+        out.push_str(&t.first_line());
+        let shown = self.max_frames.unwrap_or(usize::MAX);
+        for ste in t.stack_trace.iter().take(shown) {
+            _ = write!(out, "\n{indent}     at {ste}");
+            if let Some(cpd) = ste.cpd.as_ref().filter(|_| self.packaging_data) {
+                let approx = if cpd.exact { "" } else { "~" };
+                _ = write!(out, " {approx}[{}:{}]", cpd.code_location, cpd.version);
+            }
+        }
+        let omitted = t.stack_trace.len().saturating_sub(shown);
+        if omitted > 0 {
+            _ = write!(out, "\n{indent}     ... {omitted} more");
+        }
+    }
+}
+
+#[cfg(test)]
+fn chain() -> Throwable {
+    let mut root = Throwable::new("a.Root", &[("a.Reader", "read")]);
+    root.message = Some("disk full".into());
+    let mut ex =
+        Throwable::new("a.Wrapper", &[("a.Main", "run"), ("a.Main", "main")]).caused_by(root);
+    ex.suppressed
+        .push(Throwable::new("a.Close", &[("a.Reader", "close")]));
+    ex
+}
+
+#[test]
+fn test_render_default() {
+    assert_eq!(
+        ThrowableRenderer::new().render(&chain()),
+        "a.Wrapper\n     at a.Main.run(Main.java:1)\n     at a.Main.main(Main.java:2)\n\
+         \tSuppressed: a.Close\n\t     at a.Reader.close(Reader.java:1)\n\
+         Caused by: a.Root: disk full\n     at a.Reader.read(Reader.java:1)"
+    );
+}
+
+#[test]
+fn test_render_max_frames() {
+    let renderer = ThrowableRenderer::new()
+        .max_frames(1)
+        .suppressed(false)
+        .causes(false);
+    assert_eq!(
+        renderer.render(&chain()),
+        "a.Wrapper\n     at a.Main.run(Main.java:1)\n     ... 1 more"
+    );
+    assert_eq!(
+        renderer.max_frames(0).render(&chain()),
+        "a.Wrapper\n     ... 2 more"
+    );
+}
+
+#[test]
+fn test_render_without_suppressed() {
+    assert_eq!(
+        ThrowableRenderer::new().suppressed(false).render(&chain()),
+        "a.Wrapper\n     at a.Main.run(Main.java:1)\n     at a.Main.main(Main.java:2)\n\
+         Caused by: a.Root: disk full\n     at a.Reader.read(Reader.java:1)"
+    );
+}
+
+#[test]
+fn test_render_without_causes() {
+    assert_eq!(
+        ThrowableRenderer::new().causes(false).render(&chain()),
+        "a.Wrapper\n     at a.Main.run(Main.java:1)\n     at a.Main.main(Main.java:2)\n\
+         \tSuppressed: a.Close\n\t     at a.Reader.close(Reader.java:1)"
+    );
+}
+
+#[test]
+fn test_render_line_endings() {
+    let ex = Throwable::new("a.Error", &[("a.Main", "run"), ("a.Main", "main")]);
+    let render = |ending| ThrowableRenderer::new().line_ending(ending).render(&ex);
+    assert_eq!(
+        render(LineEnding::Lf),
+        "a.Error\n     at a.Main.run(Main.java:1)\n     at a.Main.main(Main.java:2)"
+    );
+    assert_eq!(
+        render(LineEnding::CrLf),
+        "a.Error\r\n     at a.Main.run(Main.java:1)\r\n     at a.Main.main(Main.java:2)"
+    );
+}
+
+#[test]
+fn test_render_packaging_data() {
+    use crate::ClassPackagingData;
+    let mut ex = Throwable::new("a.Error", &[("a.Main", "run"), ("a.Main", "main")]);
+    ex.stack_trace[0].cpd = Some(ClassPackagingData {
+        code_location: "app.jar".into(),
+        version: "1.2".into(),
+        exact: true,
+    });
+    ex.stack_trace[1].cpd = Some(ClassPackagingData {
+        code_location: "app.jar".into(),
+        version: "na".into(),
+        exact: false,
+    });
+    assert_eq!(
+        ThrowableRenderer::new().render(&ex),
+        "a.Error\n     at a.Main.run(Main.java:1)\n     at a.Main.main(Main.java:2)"
+    );
+    assert_eq!(
+        ThrowableRenderer::new().packaging_data(true).render(&ex),
+        "a.Error\n     at a.Main.run(Main.java:1) [app.jar:1.2]\n     \
+         at a.Main.main(Main.java:2) ~[app.jar:na]"
+    );
+}
+
+#[test]
+fn test_renderer_default() {
+    let renderer = ThrowableRenderer {
+        max_frames: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(renderer.max_depth, usize::MAX);
+    assert!(!renderer.packaging_data);
+    assert!(renderer.suppressed);
+    assert!(renderer.causes);
+    assert_eq!(renderer.line_ending, LineEnding::Lf);
+    assert_eq!(ThrowableRenderer::default().max_frames, None);
+}